@@ -0,0 +1,134 @@
+// zombies finding their way to the player, the shared flow field against a search per zombie
+//
+//   cargo bench --bench pathfind
+
+use nannou::rand::rngs::StdRng;
+use std::cmp::Reverse;
+use std::hint::black_box;
+use that_night::*;
+
+// as far from the player as the flow field looks
+const WINDOW: i32 = MOB_FOV as i32 + 18;
+
+// a* from one zombie to the player over the same step costs, gives the first step
+fn astar(map: &Map, from: (i32, i32), to: (i32, i32)) -> Option<(i32, i32)> {
+    let h = |(x, y): (i32, i32)| dist(x, y, to.0, to.1) as u32;
+    let mut best: HashMap<(i32, i32), (u32, (i32, i32))> = HashMap::new();
+    let mut open = BinaryHeap::new();
+    best.insert(from, (0, from));
+    open.push(Reverse((h(from), 0, from)));
+
+    while let Some(Reverse((_, g, p))) = open.pop() {
+        if p == to {
+            // walk back to the step right after `from`
+            let mut k = p;
+            while best[&k].1 != from {
+                k = best[&k].1;
+            }
+            return Some(k);
+        }
+        if g > best[&p].0 {
+            continue;
+        }
+        for (dx, dy) in DIRECTIONS {
+            let n = (p.0 + dx, p.1 + dy);
+            if (n.0 - to.0).abs() > WINDOW || (n.1 - to.1).abs() > WINDOW {
+                continue;
+            }
+            let cost = if n == to { Some(1) } else { step_cost(map, n) };
+            let Some(cost) = cost else { continue };
+            if matches!(map.tiles[n], Tile::MovableWall(_)) && !can_push(map, p, n, to) {
+                continue;
+            }
+            if best.get(&n).is_none_or(|&(d, _)| g + cost < d) {
+                best.insert(n, (g + cost, p));
+                open.push(Reverse((g + cost + h(n), g + cost, n)));
+            }
+        }
+    }
+    None
+}
+
+// a classic map with `n` zombies packed in the square they move in around the start
+fn setup(n: usize) -> (Map, Vec<(i32, i32)>) {
+    let mut rng = StdRng::seed_from_u64(1);
+    let params = MapParams::sized(MapSize::Large, Shape::Square);
+    let mut map = Map::new(MapLayout::generate(
+        Generator::Classic.build().as_ref(),
+        &params,
+        &mut rng,
+    ));
+    let (sx, sy) = map.start;
+    let r = MOB_FOV as i32;
+
+    for x in sx - r..sx + r {
+        for y in sy - r..sy + r {
+            if map.enemies[(x, y)].take().is_some() {
+                map.pass[(x, y)] = true;
+            }
+        }
+    }
+
+    let mut zombies = vec![];
+    while zombies.len() < n {
+        let (x, y) = (rng.gen_range(sx - r..sx + r), rng.gen_range(sy - r..sy + r));
+        if (x, y) == map.start || !map.pass[(x, y)] || !matches!(map.tiles[(x, y)], Tile::None) {
+            continue;
+        }
+        map.pass[(x, y)] = false;
+        map.enemies[(x, y)] = Some(Enemy {
+            uid: zombies.len() as i32,
+            hp: 1,
+            mhp: 1,
+            last: 0,
+            cd: 19,
+            slowed: 0,
+            immobilized: false,
+            ty: EnemyType::Zombie,
+        });
+        zombies.push((x, y));
+    }
+    (map, zombies)
+}
+
+// milliseconds per tick of `f` over `ticks` ticks
+fn time(ticks: u32, mut f: impl FnMut()) -> f64 {
+    let start = Instant::now();
+    for _ in 0..ticks {
+        f();
+    }
+    start.elapsed().as_secs_f64() * 1000.0 / ticks as f64
+}
+
+fn main() {
+    for n in [50, 200, 500, 1000] {
+        let (map, zombies) = setup(n);
+        let player = map.start;
+
+        let mut flow = FlowField::new();
+        let shared = time(200, || {
+            flow.update(&map, player);
+            for &z in &zombies {
+                black_box(flow.step(&map, z));
+            }
+        });
+        let each = time(5, || {
+            for &z in &zombies {
+                black_box(astar(&map, z, player));
+            }
+        });
+
+        let found = zombies
+            .iter()
+            .filter(|&&z| flow.step(&map, z).is_some())
+            .count();
+        println!(
+            "{:>4} zombies: flow field {:>7.3} ms/tick, a* per zombie {:>8.3} ms/tick, {:>5.1}x faster ({} with a step)",
+            n,
+            shared,
+            each,
+            each / shared,
+            found
+        );
+    }
+}
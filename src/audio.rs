@@ -0,0 +1,421 @@
+use super::*;
+
+use std::{cell::RefCell, f64::consts::TAU, sync::Arc};
+
+// total number of sound effects that can play at once
+pub const MAX_VOICES: usize = 24;
+
+// sounds quieter than this are not worth a voice
+const MIN_VOLUME: f32 = 0.005;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Priority {
+    Low,
+    Normal,
+    High,
+    // never dropped, can always steal a voice
+    Critical,
+}
+
+impl Sound {
+    pub fn priority(self) -> Priority {
+        use Sound::*;
+        match self {
+            BossAppear | BombExplosion | LowFood | Defeat => Priority::Critical,
+            Upgrade | UiSwitch | UseBomb | UseTurret | UseEmp | UseAmmo => Priority::High,
+            PickChest | BombTick | TurretShoot | ZombieDeath | ZombieHit => Priority::Normal,
+            Walking | Running | Intro | Ambient => Priority::Low,
+        }
+    }
+    // how many instances of this sound can play at once
+    pub fn voices(self) -> usize {
+        use Sound::*;
+        match self {
+            Walking => 6,
+            BombTick | TurretShoot | ZombieHit | ZombieDeath | BombExplosion => 4,
+            UseAmmo | PickChest => 3,
+            _ => 2,
+        }
+    }
+    // long tracks are decoded while playing instead of upfront
+    fn streamed(self) -> bool {
+        matches!(self, Sound::Intro | Sound::Ambient)
+    }
+}
+
+// decoded samples shared between every voice playing the same sound
+#[derive(Clone)]
+pub struct Samples {
+    channels: u16,
+    rate: u32,
+    data: Arc<[i16]>,
+}
+
+impl Samples {
+    pub fn decode(bytes: &'static [u8]) -> Self {
+        let decoder = Decoder::new(Cursor::new(bytes)).expect("Invalid sound format");
+        let (channels, rate) = (decoder.channels(), decoder.sample_rate());
+        Samples {
+            channels,
+            rate,
+            data: decoder.collect(),
+        }
+    }
+    pub fn source(&self) -> SamplesSource {
+        SamplesSource {
+            samples: self.clone(),
+            pos: 0,
+        }
+    }
+}
+
+pub struct SamplesSource {
+    samples: Samples,
+    pos: usize,
+}
+
+impl Iterator for SamplesSource {
+    type Item = i16;
+    fn next(&mut self) -> Option<i16> {
+        let sample = self.samples.data.get(self.pos).copied();
+        self.pos += 1;
+        sample
+    }
+}
+
+impl Source for SamplesSource {
+    fn current_frame_len(&self) -> Option<usize> {
+        Some(self.samples.data.len().saturating_sub(self.pos))
+    }
+    fn channels(&self) -> u16 {
+        self.samples.channels
+    }
+    fn sample_rate(&self) -> u32 {
+        self.samples.rate
+    }
+    fn total_duration(&self) -> Option<Duration> {
+        let per_sec = self.samples.rate as f64 * self.samples.channels as f64;
+        Some(Duration::from_secs_f64(self.samples.data.len() as f64 / per_sec))
+    }
+}
+
+pub enum Clip {
+    Decoded(Samples),
+    Streamed(&'static [u8]),
+}
+
+pub struct Voice {
+    pub sound: Sound,
+    pub volume: f32,
+    sink: Sink,
+}
+
+pub struct Fade {
+    sink: Sink,
+    from: f32,
+    start: Instant,
+    duration: Duration,
+}
+
+pub struct Mixer {
+    sout: OutputStreamHandle,
+    clips: Vec<Clip>,
+    voices: RefCell<Vec<Voice>>,
+    fades: RefCell<Vec<Fade>>,
+}
+
+impl Mixer {
+    pub fn new(sout: OutputStreamHandle, sounds: Vec<&'static [u8]>) -> Self {
+        let clips = SOUNDS
+            .iter()
+            .zip(sounds)
+            .map(|(&sound, bytes)| {
+                if sound.streamed() {
+                    Clip::Streamed(bytes)
+                } else {
+                    Clip::Decoded(Samples::decode(bytes))
+                }
+            })
+            .collect();
+
+        Mixer {
+            sout,
+            clips,
+            voices: RefCell::new(Vec::with_capacity(MAX_VOICES)),
+            fades: RefCell::new(vec![]),
+        }
+    }
+    fn sink(&self, sound: Sound, v: f32) -> Sink {
+        let sink = Sink::try_new(&self.sout).expect("Can't play sound");
+        match self.clips[sound as usize] {
+            Clip::Decoded(ref samples) => sink.append(samples.source()),
+            Clip::Streamed(bytes) => {
+                sink.append(Decoder::new(Cursor::new(bytes)).expect("Invalid sound format"))
+            }
+        }
+        sink.set_volume(v);
+        sink
+    }
+    // plays a pooled sound effect, possibly stealing a voice from a less important one
+    pub fn play(&self, sound: Sound, v: f32) {
+        let priority = sound.priority();
+        let critical = priority == Priority::Critical;
+
+        if v < MIN_VOLUME && !critical {
+            return;
+        }
+
+        let mut voices = self.voices.borrow_mut();
+        voices.retain(|voice| !voice.sink.empty());
+
+        // per sound limit, replace the quietest instance
+        if voices.iter().filter(|voice| voice.sound == sound).count() >= sound.voices() {
+            let (idx, quietest) = voices
+                .iter()
+                .enumerate()
+                .filter(|(_, voice)| voice.sound == sound)
+                .min_by(|(_, a), (_, b)| a.volume.total_cmp(&b.volume))
+                .unwrap();
+
+            if quietest.volume > v && !critical {
+                return;
+            }
+            voices.swap_remove(idx);
+        }
+
+        // global limit, replace the least important voice
+        if voices.len() >= MAX_VOICES {
+            let (idx, weakest) = voices
+                .iter()
+                .enumerate()
+                .min_by(|(_, a), (_, b)| {
+                    a.sound
+                        .priority()
+                        .cmp(&b.sound.priority())
+                        .then(a.volume.total_cmp(&b.volume))
+                })
+                .unwrap();
+
+            let weaker = (weakest.sound.priority(), weakest.volume) < (priority, v);
+            if weaker {
+                voices.swap_remove(idx);
+            } else if !critical {
+                return;
+            }
+        }
+
+        voices.push(Voice {
+            sound,
+            volume: v,
+            sink: self.sink(sound, v),
+        });
+    }
+    // plays a sound outside of the pool, the caller owns the playback
+    pub fn play_get(&self, sound: Sound, v: f32) -> Sink {
+        self.sink(sound, v)
+    }
+    // like `play_get` for anything that isn't one of the loaded sounds
+    pub fn play_source(&self, source: impl Source<Item = f32> + Send + 'static, v: f32) -> Sink {
+        let sink = Sink::try_new(&self.sout).expect("Can't play sound");
+        sink.append(source);
+        sink.set_volume(v);
+        sink
+    }
+    // takes over the sink and fades it out, stepped from `update`
+    pub fn fade(&self, sink: Sink, dur: u64) {
+        self.fades.borrow_mut().push(Fade {
+            from: sink.volume(),
+            sink,
+            start: Instant::now(),
+            duration: Duration::from_millis(dur),
+        });
+    }
+    pub fn update(&self) {
+        // finished fades are dropped which stops their sinks
+        self.fades.borrow_mut().retain(|fade| {
+            let p = fade.start.elapsed().as_secs_f32() / fade.duration.as_secs_f32();
+            fade.sink.set_volume(fade.from * (1.0 - p).max(0.0));
+            p < 1.0 && !fade.sink.empty()
+        });
+    }
+}
+
+// how threatening the situation around the player is, both in 0..=1
+pub struct Intensity {
+    pub danger: f32,
+    pub starving: f32,
+}
+
+impl Intensity {
+    pub fn measure(map: &Map, player: &Player) -> Self {
+        let r = MOB_FOV as i32 / 2;
+
+        let mut near = 0;
+        for x in max(0.0, (player.x - r) as f32)..min((player.x + r + 1) as f32, map.w as f32) {
+            for y in max(0.0, (player.y - r) as f32)..min((player.y + r + 1) as f32, map.h as f32)
+            {
+                if map.enemies[(x, y)].is_some() {
+                    near += 1;
+                }
+            }
+        }
+
+        let mut danger = (near as f32 / 12.0).min(1.0);
+        if map.boss.pos.is_some() {
+            danger = danger.max(0.6);
+        }
+
+        let food = player[Food] as f32 / player[MaxFood] as f32;
+
+        Intensity {
+            danger,
+            starving: (1.0 - food / 0.25).clamp(0.0, 1.0),
+        }
+    }
+    // how much of a layer should be heard, the calm track is pulled back under the others
+    fn weight(&self, layer: Layer) -> f32 {
+        match layer {
+            Layer::Calm => 1.0 - 0.7 * self.danger.max(self.starving),
+            Layer::Danger => self.danger,
+            Layer::Starving => self.starving,
+        }
+    }
+}
+
+// parts of the music crossfaded by intensity, the ambient track and two synthesized ones
+// under it, made up while playing so they loop without a seam
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Layer {
+    Calm,
+    // a heartbeat over a sub drone
+    Danger,
+    // two low tones beating against each other
+    Starving,
+}
+
+impl Layer {
+    pub const ALL: [Layer; 3] = [Layer::Calm, Layer::Danger, Layer::Starving];
+
+    // volume at full weight
+    fn volume(self) -> f32 {
+        match self {
+            Layer::Calm => 0.25,
+            Layer::Danger => 0.5,
+            Layer::Starving => 0.35,
+        }
+    }
+    fn play(self, state: &State, v: f32) -> Sink {
+        match self {
+            Layer::Calm => state.play_get(Sound::Ambient, v),
+            _ => state.play_source(Synth { layer: self, n: 0 }, v),
+        }
+    }
+}
+
+pub struct Synth {
+    layer: Layer,
+    // samples played so far
+    n: u64,
+}
+
+impl Synth {
+    const RATE: u32 = 44_100;
+    // seconds between heartbeats
+    const BEAT: f64 = 60.0 / 84.0;
+}
+
+impl Iterator for Synth {
+    type Item = f32;
+    fn next(&mut self) -> Option<f32> {
+        let t = self.n as f64 / Self::RATE as f64;
+        self.n += 1;
+
+        let tone = |hz: f64, t: f64| (TAU * hz * t).sin();
+        // a low thump that dies off fast, silent before it starts
+        let thump = |t: f64| {
+            if t < 0.0 {
+                0.0
+            } else {
+                tone(48.0, t) * (-14.0 * t).exp()
+            }
+        };
+
+        let sample = match self.layer {
+            // played from the ambient track
+            Layer::Calm => 0.0,
+            Layer::Danger => {
+                let p = t % Self::BEAT;
+                0.8 * thump(p) + 0.55 * thump(p - 0.2) + 0.1 * tone(41.0, t)
+            }
+            Layer::Starving => {
+                let swell = 0.6 + 0.4 * tone(0.25, t);
+                swell * (0.3 * tone(55.0, t) + 0.3 * tone(58.3, t) + 0.08 * tone(110.0, t))
+            }
+        };
+        Some(sample as f32)
+    }
+}
+
+impl Source for Synth {
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+    fn channels(&self) -> u16 {
+        1
+    }
+    fn sample_rate(&self) -> u32 {
+        Self::RATE
+    }
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+}
+
+struct Track {
+    layer: Layer,
+    sink: Option<Sink>,
+    weight: f32,
+}
+
+// every layer plays at once and their volumes follow the intensity
+pub struct Music {
+    tracks: Vec<Track>,
+}
+
+impl Music {
+    // fraction of a crossfade the layers move per frame
+    const STEP: f32 = 1.0 / 90.0;
+
+    pub fn new() -> Self {
+        Music {
+            tracks: Layer::ALL
+                .iter()
+                .map(|&layer| Track {
+                    layer,
+                    sink: None,
+                    weight: 0.0,
+                })
+                .collect(),
+        }
+    }
+    pub fn update(&mut self, state: &State, intensity: &Intensity) {
+        for track in &mut self.tracks {
+            let target = intensity.weight(track.layer);
+            track.weight += (target - track.weight).clamp(-Self::STEP, Self::STEP);
+            let volume = track.layer.volume() * track.weight;
+
+            // the ambient track ends and starts over, the synthesized ones never do
+            match track.sink {
+                Some(ref s) if !s.empty() => s.set_volume(volume),
+                _ => track.sink = Some(track.layer.play(state, volume)),
+            }
+        }
+    }
+    pub fn fade(self, state: &State, dur: u64) {
+        for track in self.tracks {
+            if let Some(s) = track.sink {
+                s.fade(state, dur);
+            }
+        }
+    }
+}
@@ -0,0 +1,286 @@
+// builds a map without opening a window and dumps it with some numbers, for tuning the generators
+//
+//   cargo run --bin mapgen -- --seed 7 --generator caves --size huge --out caves.png
+//
+// the text dump is the map file format with the stats as comments, so it loads back as an arena
+
+use nannou::image::{Rgb, RgbImage};
+use nannou::rand::rngs::StdRng;
+use that_night::*;
+
+static USAGE: &str = "usage: mapgen [options]
+
+  --seed N          terrain seed, random when left out
+  --generator NAME  classic, rooms, caves, city, field or an arena: pit, crossroads, fortress
+  --size NAME       small, medium, large or huge
+  --shape NAME      square, wide, circle or islands
+  --density F       how much of the map gets covered, 1.0 by default
+  --out FILE        a .png image, anything else gets the text format, stdout if left out
+  --scale N         pixels per tile in the image, 2 by default
+
+zombies and chests are placed like the game does at the start of a run, they don't follow the seed";
+
+struct Args {
+    seed: u64,
+    source: MapSource,
+    size: MapSize,
+    shape: Shape,
+    density: f32,
+    out: Option<String>,
+    scale: u32,
+}
+
+// the part of an id after the dot, `map.caves` is `caves`
+fn named<T: Copy>(
+    all: &[T],
+    id: fn(T) -> &'static str,
+    what: &str,
+    name: &str,
+) -> std::result::Result<T, String> {
+    let short = |t: T| id(t).split('.').next_back().unwrap_or_default();
+    all.iter()
+        .copied()
+        .find(|&t| short(t) == name)
+        .ok_or_else(|| {
+            let mut names: Vec<_> = all.iter().map(|&t| short(t)).collect();
+            names.dedup();
+            format!(
+                "unknown {} '{}', expected one of {}",
+                what,
+                name,
+                names.join(", ")
+            )
+        })
+}
+
+fn parse() -> std::result::Result<Args, String> {
+    let mut args = Args {
+        seed: thread_rng().gen(),
+        source: MapSource::Generated(Generator::Classic),
+        size: MapSize::Large,
+        shape: Shape::Square,
+        density: 1.0,
+        out: None,
+        scale: 2,
+    };
+
+    let mut it = std::env::args().skip(1);
+    while let Some(flag) = it.next() {
+        if flag == "--help" || flag == "-h" {
+            println!("{}", USAGE);
+            std::process::exit(0);
+        }
+        let value = it
+            .next()
+            .ok_or_else(|| format!("{} needs a value\n\n{}", flag, USAGE))?;
+        let number = || format!("{} expects a number, got '{}'", flag, value);
+
+        match flag.as_str() {
+            "--seed" => args.seed = value.parse().map_err(|_| number())?,
+            "--generator" => {
+                args.source = named(&MapSource::all(), MapSource::id, "generator", &value)?
+            }
+            "--size" => args.size = named(&MapSize::ALL, MapSize::id, "size", &value)?,
+            "--shape" => args.shape = named(&Shape::ALL, Shape::id, "shape", &value)?,
+            "--density" => args.density = value.parse().map_err(|_| number())?,
+            "--out" => args.out = Some(value),
+            "--scale" => args.scale = value.parse::<u32>().map_err(|_| number())?.max(1),
+            _ => return Err(format!("unknown option {}\n\n{}", flag, USAGE)),
+        }
+    }
+    Ok(args)
+}
+
+fn build(args: &Args) -> std::result::Result<Map, String> {
+    let layout = match args.source {
+        MapSource::Generated(g) => {
+            let params = MapParams {
+                density: args.density,
+                ..MapParams::sized(args.size, args.shape)
+            };
+            MapLayout::generate(
+                g.build().as_ref(),
+                &params,
+                &mut StdRng::seed_from_u64(args.seed),
+            )
+        }
+        MapSource::Bundled(arena) => arena.layout(),
+        MapSource::Endless(_) => {
+            return Err(String::from("the endless mode has no whole map to dump"))
+        }
+    };
+    Ok(Map::new(layout))
+}
+
+fn stats(map: &Map, args: &Args) -> Vec<String> {
+    let (w, h) = (map.w, map.h);
+    let (sx, sy) = map.start;
+    let count = |f: &dyn Fn(&Tile) -> bool| {
+        (0..w * h)
+            .filter(|&i| f(&map.tiles[(i % w, i / w)]))
+            .count()
+    };
+    let walls = count(&|t| matches!(t, Tile::Wall(_)));
+    let movable = count(&|t| matches!(t, Tile::MovableWall(_)));
+    let doors = count(&|t| matches!(t, Tile::Door(_)));
+    let water = count(&|t| matches!(t, Tile::Water));
+    let spikes = count(&|t| matches!(t, Tile::Spikes));
+    let open = (w * h) as usize - walls;
+    let reach = map.reach.values().filter(|&&r| r).count();
+
+    let zombies: Vec<_> = (0..w * h)
+        .map(|i| (i % w, i / w))
+        .filter(|&p| map.enemies[p].is_some())
+        .collect();
+    let closest = zombies
+        .iter()
+        .map(|&(x, y)| dist(x, y, sx, sy))
+        .min()
+        .unwrap_or_default();
+
+    // quadrants around the start
+    let mut quadrants = [0; 4];
+    let mut from_start = vec![];
+    for &(x, y) in &map.chests {
+        quadrants[(x >= sx) as usize + 2 * (y >= sy) as usize] += 1;
+        from_start.push(dist(x, y, sx, sy));
+    }
+    from_start.sort_unstable();
+    let mean = from_start.iter().sum::<i32>() as f32 / from_start.len().max(1) as f32;
+
+    let percent = |n: usize, of: usize| 100.0 * n as f32 / of.max(1) as f32;
+    vec![
+        format!(
+            "{}, seed {}, {}x{}, density {:.2}",
+            args.source.id(),
+            args.seed,
+            w,
+            h,
+            args.density
+        ),
+        format!(
+            "walls {:.1}% ({} solid, {} movable)",
+            percent(walls + movable, (w * h) as usize),
+            walls,
+            movable
+        ),
+        format!("doors {}, water {}, spikes {}", doors, water, spikes),
+        format!(
+            "reachable {} tiles, {:.1}% of open ground",
+            reach,
+            percent(reach, open)
+        ),
+        format!(
+            "zombies {}, closest {} from the start",
+            zombies.len(),
+            closest
+        ),
+        format!(
+            "chests {}, {:.1} per 10k reachable tiles",
+            map.chests.len(),
+            10_000.0 * map.chests.len() as f32 / reach.max(1) as f32
+        ),
+        format!(
+            "chests by quadrant: nw {}, ne {}, sw {}, se {}",
+            quadrants[0], quadrants[1], quadrants[2], quadrants[3]
+        ),
+        format!(
+            "chests from the start: min {}, median {}, mean {:.0}, max {}",
+            from_start.first().unwrap_or(&0),
+            from_start.get(from_start.len() / 2).unwrap_or(&0),
+            mean,
+            from_start.last().unwrap_or(&0)
+        ),
+    ]
+}
+
+// the map file legend, chests and zombies mark where they stand
+fn glyph(map: &Map, (x, y): (i32, i32)) -> char {
+    if (x, y) == map.start {
+        return '@';
+    }
+    if map.enemies[(x, y)].is_some() {
+        return 'z';
+    }
+    match map.tiles[(x, y)] {
+        Tile::None => '.',
+        Tile::Wall(_) => '#',
+        Tile::MovableWall(_) => '+',
+        Tile::Chest(_) => 'c',
+        Tile::Door(_) => 'D',
+        Tile::Water => '~',
+        Tile::Spikes => '^',
+        Tile::Barricade(_) => '=',
+    }
+}
+
+fn color(map: &Map, theme: &Theme, (x, y): (i32, i32)) -> Color {
+    if (x, y) == map.start {
+        return theme.player;
+    }
+    if map.enemies[(x, y)].is_some() {
+        return theme.zombie;
+    }
+    match map.tiles[(x, y)] {
+        Tile::None => theme.floor,
+        Tile::Wall(_) => theme.wall,
+        Tile::MovableWall(_) => theme.movable_wall,
+        Tile::Chest(ref chest) => chest.ty.color(theme, 0),
+        Tile::Door(_) => theme.door,
+        Tile::Water => theme.water,
+        Tile::Spikes => theme.spikes,
+        Tile::Barricade(_) => theme.barricade,
+    }
+}
+
+fn text(map: &Map, stats: &[String]) -> String {
+    let mut out = String::new();
+    for line in stats {
+        out += &format!("; {}\n", line);
+    }
+    for y in 0..map.h {
+        out.extend((0..map.w).map(|x| glyph(map, (x, y))));
+        out.push('\n');
+    }
+    out
+}
+
+fn image(map: &Map, scale: u32) -> RgbImage {
+    let theme = Theme::default();
+    RgbImage::from_fn(map.w as u32 * scale, map.h as u32 * scale, |x, y| {
+        Rgb(color(map, &theme, ((x / scale) as i32, (y / scale) as i32)))
+    })
+}
+
+fn run() -> std::result::Result<(), String> {
+    let args = parse()?;
+    let map = build(&args)?;
+    let stats = stats(&map, &args);
+
+    match args.out {
+        Some(ref path) if path.ends_with(".png") => {
+            image(&map, args.scale)
+                .save(path)
+                .map_err(|e| format!("Failed to write {}: {}", path, e))?;
+            for line in &stats {
+                println!("{}", line);
+            }
+        }
+        Some(ref path) => {
+            std::fs::write(path, text(&map, &stats))
+                .map_err(|e| format!("Failed to write {}: {}", path, e))?;
+            for line in &stats {
+                println!("{}", line);
+            }
+        }
+        None => print!("{}", text(&map, &stats)),
+    }
+    Ok(())
+}
+
+fn main() {
+    if let Err(e) = run() {
+        eprintln!("{}", e);
+        std::process::exit(2);
+    }
+}
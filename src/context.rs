@@ -1,10 +1,28 @@
 use super::*;
 
+// length of one simulation tick, the game runs at 60 ticks per second
+pub const TICK: Duration = Duration::from_nanos(1_000_000_000 / 60);
+
+// after a stall at most this many ticks are caught up, the rest is dropped
+const MAX_TICKS: u32 = 5;
+
+pub const RESOLUTIONS: [(u32, u32); 6] = [
+    (1280, 720),
+    (1366, 768),
+    (1600, 900),
+    (1920, 1080),
+    (2560, 1440),
+    (3840, 2160),
+];
+
 pub struct Context {
     pub state: State,
     pub frames: usize,
     pub last: Instant,
+    pub acc: Duration,
     pub screen: Screen,
+    // input that doesn't arrive through window events
+    pub sources: Vec<Box<dyn InputSource>>,
 }
 
 impl Context {
@@ -16,8 +34,21 @@ impl Context {
             .run();
     }
     pub fn new(app: &App) -> Self {
+        let mut storage = Storage::new();
+
+        // nannou keeps the surface configuration it was built with, so vsync only
+        // takes effect when the window is created
+        let present_mode = if storage.display.vsync {
+            wgpu::PresentMode::AutoVsync
+        } else {
+            wgpu::PresentMode::AutoNoVsync
+        };
+
         app.new_window()
             .size(0, 0)
+            .surface_conf_builder(
+                window::SurfaceConfigurationBuilder::new().present_mode(present_mode),
+            )
             .decorations(false)
             .title("That Night")
             .key_pressed(Context::pressed)
@@ -28,32 +59,91 @@ impl Context {
 
         app.set_exit_on_escape(false);
 
+        Context::apply_display(app, &storage.display);
+
         let (w, h) = app.main_window().inner_size_pixels();
 
         let (stream, sout) = OutputStream::try_default().unwrap();
-
-        app.main_window().set_fullscreen(true);
+        // the font a saved language needs may have gone missing since
+        let mut lang = Lang::load(storage.language);
+        if !lang.usable() {
+            storage.language = Language::English;
+            lang = Lang::load(Language::English);
+        }
 
         Self {
             state: State {
                 frame: 0,
-                storage: Storage::new(),
-                keys: [false; 256],
-                font: font(),
+                alpha: 0.0,
+                theme: Theme::load(storage.display.theme),
+                storage,
+                held: Held::default(),
+                font: lang.font().unwrap_or_else(font),
+                lang,
                 w: w as f32,
                 h: h as f32,
                 stream,
-                sout,
-                sounds: sounds(),
+                mixer: Mixer::new(sout, sounds()),
             },
             frames: 0,
             last: Instant::now(),
+            acc: Duration::ZERO,
             screen: Screen::Loading(Loading::new()),
+            sources: vec![Box::new(Gamepads::new())],
+        }
+    }
+    pub fn apply_display(app: &App, display: &Display) {
+        let window = app.main_window();
+        let monitor = app
+            .available_monitors()
+            .get(display.monitor)
+            .cloned()
+            .or_else(|| app.primary_monitor());
+
+        match display.mode {
+            WindowMode::Fullscreen => {
+                // exact size if the monitor supports it, highest refresh rate first
+                let mode = monitor.and_then(|monitor| {
+                    let mut modes = monitor.video_modes().collect::<Vec<_>>();
+                    modes.sort_by_key(|mode| std::cmp::Reverse(mode.refresh_rate_millihertz()));
+                    let exact = modes.iter().position(|mode| {
+                        let size = mode.size();
+                        (size.width, size.height) == display.size
+                    });
+                    exact
+                        .or((!modes.is_empty()).then_some(0))
+                        .map(|k| modes.swap_remove(k))
+                });
+
+                window.set_decorations(false);
+                window.set_fullscreen_with(mode.map(Fullscreen::Exclusive));
+            }
+            WindowMode::Borderless => {
+                window.set_decorations(false);
+                window.set_fullscreen_with(Some(Fullscreen::Borderless(monitor)));
+            }
+            WindowMode::Windowed => {
+                let (w, h) = display.size;
+
+                window.set_fullscreen_with(None);
+                window.set_decorations(true);
+                window.set_inner_size_pixels(w, h);
+
+                if let Some(monitor) = monitor {
+                    let (pos, size) = (monitor.position(), monitor.size());
+                    window
+                        .winit_window()
+                        .set_outer_position(winit::dpi::PhysicalPosition::new(
+                            pos.x + (size.width as i32 - w as i32) / 2,
+                            pos.y + (size.height as i32 - h as i32) / 2,
+                        ));
+                }
+            }
         }
     }
     pub fn render(app: &App, ctx: &Self, frame: Frame) {
         let draw = app.draw();
-        draw.background().color(BLACK);
+        draw.background().color(ctx.state.theme.background.rgb());
 
         match ctx.screen {
             Screen::Loading(ref screen) => {
@@ -84,59 +174,90 @@ impl Context {
         //     ctx.frames = 0;
         //     ctx.last = Instant::now();
         // }
-        let mut dt = ctx.last.elapsed().as_millis();
-        while dt < 16 {
-            dt = ctx.last.elapsed().as_millis();
-        }
+        let now = Instant::now();
+        ctx.acc = (ctx.acc + (now - ctx.last)).min(TICK * MAX_TICKS);
+        ctx.last = now;
 
         let (w, h) = app.main_window().inner_size_pixels();
 
+        ctx.state.mixer.update();
+
         ctx.state.w = w as f32;
         ctx.state.h = h as f32;
 
-        if let Some(new) = match ctx.screen {
-            Screen::Loading(ref mut screen) => screen.update(app, &mut ctx.state),
-            Screen::Menu(ref mut screen) => screen.update(app, &mut ctx.state),
-            Screen::Tutorial(ref mut screen) => screen.update(app, &mut ctx.state),
-            Screen::Playing(ref mut screen) => screen.update(app, &mut ctx.state),
-            Screen::Settings(ref mut screen) => screen.update(app, &mut ctx.state),
+        let bindings = &ctx.state.storage.bindings;
+        for (input, down, action) in ctx.state.held.drain(&mut ctx.sources, bindings) {
+            if down {
+                ctx.press(app, input, action);
+            } else {
+                ctx.release(app, input);
+            }
+        }
+
+        while ctx.acc >= TICK {
+            ctx.acc -= TICK;
+            ctx.tick(app);
+        }
+
+        ctx.state.alpha = ctx.acc.as_secs_f32() / TICK.as_secs_f32();
+    }
+    fn tick(&mut self, app: &App) {
+        if let Some(new) = match self.screen {
+            Screen::Loading(ref mut screen) => screen.update(app, &mut self.state),
+            Screen::Menu(ref mut screen) => screen.update(app, &mut self.state),
+            Screen::Tutorial(ref mut screen) => screen.update(app, &mut self.state),
+            Screen::Playing(ref mut screen) => screen.update(app, &mut self.state),
+            Screen::Settings(ref mut screen) => screen.update(app, &mut self.state),
             _ => None,
         } {
-            ctx.screen = *new;
+            self.screen = *new;
         }
 
-        ctx.last = Instant::now();
-
-        ctx.state.frame += 1;
+        self.state.frame += 1;
     }
     pub fn pressed(app: &App, ctx: &mut Self, k: Key) {
-        ctx.state.keys[k as usize] = true;
-
-        match ctx.screen {
+        let input = Input::Key(k);
+        let action = ctx
+            .state
+            .held
+            .feed(&ctx.state.storage.bindings, input, true);
+        ctx.press(app, input, action);
+    }
+    pub fn released(app: &App, ctx: &mut Self, k: Key) {
+        let input = Input::Key(k);
+        ctx.state
+            .held
+            .feed(&ctx.state.storage.bindings, input, false);
+        ctx.release(app, input);
+    }
+    // `held` already has the press, `action` is what it triggered
+    fn press(&mut self, app: &App, input: Input, action: Option<Action>) {
+        match self.screen {
             Screen::Menu(ref mut screen) => {
-                screen.pressed(app, &mut ctx.state, k);
+                screen.pressed(app, &mut self.state, input);
             }
             Screen::Tutorial(ref mut screen) => {
-                screen.pressed(app, &mut ctx.state, k);
+                screen.pressed(app, &mut self.state, input);
             }
             Screen::Playing(ref mut screen) => {
-                screen.pressed(app, &mut ctx.state, k);
+                screen.pressed(app, &mut self.state, input, action);
             }
             Screen::Settings(ref mut screen) => {
-                screen.pressed(app, &mut ctx.state, k);
+                screen.pressed(app, &mut self.state, input);
             }
             _ => {}
         }
     }
-    pub fn released(app: &App, ctx: &mut Self, k: Key) {
-        ctx.state.keys[k as usize] = false;
-
-        match ctx.screen {
+    fn release(&mut self, app: &App, input: Input) {
+        match self.screen {
             Screen::Menu(ref mut screen) => {
-                screen.released(app, &mut ctx.state, k);
+                screen.released(app, &mut self.state, input);
             }
             Screen::Playing(ref mut screen) => {
-                screen.released(app, &mut ctx.state, k);
+                screen.released(app, &mut self.state, input);
+            }
+            Screen::Settings(ref mut screen) => {
+                screen.released(app, &mut self.state, input);
             }
             _ => {}
         }
@@ -146,6 +267,9 @@ impl Context {
             Screen::Menu(ref mut screen) => {
                 screen.mpressed(app, &mut ctx.state, mb);
             }
+            Screen::Playing(ref mut screen) => {
+                screen.mpressed(app, &mut ctx.state, mb);
+            }
             Screen::Settings(ref mut screen) => {
                 screen.mpressed(app, &mut ctx.state, mb);
             }
@@ -156,32 +280,35 @@ impl Context {
 
 pub struct State {
     frame: i32,
+    // how far the renderer is between the last tick and the next one
+    pub alpha: f32,
     pub storage: Storage,
-    pub keys: [bool; 256],
+    pub theme: Theme,
+    pub held: Held,
     pub font: Font,
+    pub lang: Lang,
     pub w: f32,
     pub h: f32,
     stream: OutputStream,
-    sout: OutputStreamHandle,
-    sounds: Vec<Cursor<Vec<u8>>>,
+    mixer: Mixer,
 }
 
 impl State {
+    // integer ui scale so the pixel font stays crisp on large displays
+    pub fn scale(&self) -> f32 {
+        (self.h / 1080.0).floor().max(1.0)
+    }
+    pub fn held(&self, action: Action) -> bool {
+        self.held.holds(&self.storage.bindings, action)
+    }
     pub fn play(&self, sound: Sound, v: f32) {
-        let s = self
-            .sout
-            .play_once(self.sounds[sound as usize].clone())
-            .expect("Can't play sound");
-        s.set_volume(v);
-        s.detach();
+        self.mixer.play(sound, v);
     }
     pub fn play_get(&self, sound: Sound, v: f32) -> Sink {
-        let s = self
-            .sout
-            .play_once(self.sounds[sound as usize].clone())
-            .expect("Can't play sound");
-        s.set_volume(v);
-        s
+        self.mixer.play_get(sound, v)
+    }
+    pub fn play_source(&self, source: impl Source<Item = f32> + Send + 'static, v: f32) -> Sink {
+        self.mixer.play_source(source, v)
     }
 }
 
@@ -217,7 +344,7 @@ pub const SOUNDS: [Sound; 19] = [
     Sound::Running,
 ];
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[repr(u8)]
 pub enum Sound {
     Intro,
@@ -251,27 +378,20 @@ pub enum Sound {
 }
 
 pub trait SinkExt {
-    fn fade(self, dur: u64);
+    fn fade(self, state: &State, dur: u64);
 }
 
 impl SinkExt for Sink {
-    fn fade(self, dur: u64) {
-        spawn(move || {
-            let current = self.volume();
-            let inv = 1.0 / (dur / 20) as f32;
-            for k in 0..dur / 20 {
-                self.set_volume(current - k as f32 * inv * current);
-                sleep(Duration::from_millis(20));
-            }
-        });
+    fn fade(self, state: &State, dur: u64) {
+        state.mixer.fade(self, dur);
     }
 }
 
-fn font() -> Font {
+pub fn font() -> Font {
     Font::from_bytes(include_bytes!("../assets/m5x7.ttf")).expect("Invalid font format")
 }
 
-fn sounds() -> Vec<Cursor<Vec<u8>>> {
+fn sounds() -> Vec<&'static [u8]> {
     let mut r = vec![];
     for bytes in [
         &include_bytes!("../assets/Intro.mp3")[..],
@@ -294,7 +414,7 @@ fn sounds() -> Vec<Cursor<Vec<u8>>> {
         &include_bytes!("../assets/Walking.mp3")[..],
         &include_bytes!("../assets/Running.mp3")[..],
     ] {
-        r.push(Cursor::new(Vec::from(&bytes[..])));
+        r.push(bytes);
     }
     r
 }
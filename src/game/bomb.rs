@@ -48,7 +48,7 @@ pub fn check_bomb(player: &mut Player, map: &mut Map, state: &mut State, frame:
 pub fn update_bombs(map: &mut Map, player: &mut Player, state: &mut State, frame: i32) {
     let mut rng = thread_rng();
     for i in 0..map.bombs.len() {
-        let bomb = &map.bombs[i];
+        let bomb = map.bombs[i].clone();
         let (x, y) = (bomb.x, bomb.y);
         let mut rng = thread_rng();
 
@@ -70,6 +70,11 @@ pub fn update_bombs(map: &mut Map, player: &mut Player, state: &mut State, frame
         }
 
         if frame == bomb.start + bomb.duration - 10 {
+            map.events.push(GameEvent::Explosion {
+                x: bomb.x,
+                y: bomb.y,
+                radius: bomb.radius,
+            });
 
             // destroy blocks around bomb (plus sign)
             for (dx, dy) in [(-1, 0), (0, 1), (1, 0), (0, -1)] {
@@ -81,9 +86,14 @@ pub fn update_bombs(map: &mut Map, player: &mut Player, state: &mut State, frame
                     let (x, y) = (bomb.x + dx, bomb.y + dy);
 
                     match map.tiles[(x, y)] {
-                        Tile::Wall(Wall { bullets })
-                        | Tile::MovableWall(MovableWall { bullets }) => {
-                            for _ in 0..bullets {
+                        // what was chipped off a worn wall flies out again
+                        Tile::Wall(Wall { hp }) | Tile::MovableWall(MovableWall { hp }) => {
+                            let whole = if matches!(map.tiles[(x, y)], Tile::Wall(_)) {
+                                WALL_HP
+                            } else {
+                                MOVABLE_WALL_HP
+                            };
+                            for _ in 0..whole - hp {
                                 let (dx, dy) = DIRECTIONS[rng.gen_range(0..DIRECTIONS.len())];
 
                                 map.bullets.push(Bullet {
@@ -110,11 +120,14 @@ pub fn update_bombs(map: &mut Map, player: &mut Player, state: &mut State, frame
                                 .unwrap();
                             map.chests.remove(idx);
                         }
+                        // there's nothing to blow up in water or spikes
+                        Tile::Water | Tile::Spikes => continue,
                         _ => {}
                     }
 
                     map.tiles[(x, y)] = Tile::None;
                     map.pass[(x, y)] = true;
+                    join(&mut map.reach, &map.tiles, (x, y));
                 }
             }
             // change walls to movable walls
@@ -126,9 +139,11 @@ pub fn update_bombs(map: &mut Map, player: &mut Player, state: &mut State, frame
                     && bomb.y + dy < map.h - 1
                     && matches!(map.tiles[(x, y)], Tile::Wall(_))
                 {
-                    let Tile::Wall(Wall { bullets }) = map.tiles[(x, y)] else { unreachable!() };
+                    let Tile::Wall(Wall { hp }) = map.tiles[(x, y)] else { unreachable!() };
 
-                    map.tiles[(x, y)] = Tile::MovableWall(MovableWall { bullets });
+                    map.tiles[(x, y)] = Tile::MovableWall(MovableWall {
+                        hp: hp.min(MOVABLE_WALL_HP),
+                    });
                 }
             }
             for x in max(1.0, (bomb.x - bomb.radius) as f32)
@@ -143,21 +158,40 @@ pub fn update_bombs(map: &mut Map, player: &mut Player, state: &mut State, frame
                         (bomb.y + ((bomb.x - x).abs() - bomb.radius).abs()) as f32,
                     )
                 {
+                    // walls in the blast crack a little more every time
+                    map.chip(x, y, bomb.dmg as i8);
+
                     if let Some(ref mut enemy) = map.enemies[(x, y)] {
                         enemy.hp -= bomb.dmg as i8;
+                        map.events.push(GameEvent::Damage {
+                            x,
+                            y,
+                            amount: bomb.dmg as i32,
+                        });
 
                         if enemy.hp <= 0 {
                             player.progress.killed += 1;
 
                             map.pass[(x, y)] = true;
+                            map.events.push(GameEvent::Death {
+                                x,
+                                y,
+                                boss: matches!(enemy.ty, EnemyType::ZombieBoss(_)),
+                            });
 
                             if let EnemyType::ZombieBoss(_) = enemy.ty {
                                 map.boss.pos = None;
                                 player[Score] += 100;
+                                map.events.push(GameEvent::Score { x, y, amount: 100 });
                                 player.paused = true;
                                 player.upgrading = Some(Upgrading::new(player, state));
                             } else {
                                 player[Score] += player[ScoreBomb];
+                                map.events.push(GameEvent::Score {
+                                    x,
+                                    y,
+                                    amount: player[ScoreBomb],
+                                });
                             }
 
                             map.enemies[(x, y)].take();
@@ -16,7 +16,7 @@ pub struct Bullet {
 }
 
 pub fn check_shot(
-    keys: &Vec<Key>,
+    dir: Option<usize>,
     player: &mut Player,
     bullets: &mut Vec<Bullet>,
     state: &State,
@@ -26,17 +26,8 @@ pub fn check_shot(
         return;
     }
 
-    let Some(&last_key) = keys.last() else { return };
-
-    let (dx, dy) = match last_key {
-        _ if last_key as u32 == state.storage.hotkeys[Hotkey::Up as usize] => (0, -1),
-        _ if last_key as u32 == state.storage.hotkeys[Hotkey::Right as usize] => (1, 0),
-        _ if last_key as u32 == state.storage.hotkeys[Hotkey::Down as usize] => (0, 1),
-        _ if last_key as u32 == state.storage.hotkeys[Hotkey::Left as usize] => (-1, 0),
-        _ => {
-            return;
-        }
-    };
+    let Some(dir) = dir else { return };
+    let (dx, dy) = DIRECTIONS[dir];
     let (x, y) = (player.x, player.y);
     let mut rng = thread_rng();
 
@@ -81,6 +72,11 @@ pub fn check_bullets(map: &mut Map, player: &mut Player, state: &mut State, fram
                 bullet.pierce -= 1;
                 bullet.fork -= 1;
                 enemy.hp -= bullet.dmg;
+                map.events.push(GameEvent::Damage {
+                    x,
+                    y,
+                    amount: bullet.dmg as i32,
+                });
 
                 if bullet.fork >= 0 {
                     let (dx, dy) = if bullet.dx == 0 { (1, 0) } else { (0, 1) };
@@ -112,11 +108,21 @@ pub fn check_bullets(map: &mut Map, player: &mut Player, state: &mut State, fram
                     player.progress.killed += 1;
 
                     map.pass[(x, y)] = true;
+                    map.events.push(GameEvent::Death {
+                        x,
+                        y,
+                        boss: matches!(enemy.ty, EnemyType::ZombieBoss(_)),
+                    });
 
                     let mut rng = thread_rng();
                     match enemy.ty {
                         EnemyType::Zombie => {
                             player[Score] += player[ScoreAmmo];
+                            map.events.push(GameEvent::Score {
+                                x,
+                                y,
+                                amount: player[ScoreAmmo],
+                            });
 
                             if rng.gen_range(0..100) < 65 + player[Luck] {
                                 spawn_chest(map, frame, x, y, ChestType::random());
@@ -125,6 +131,7 @@ pub fn check_bullets(map: &mut Map, player: &mut Player, state: &mut State, fram
                         EnemyType::ZombieBoss(_) => {
                             map.boss.pos = None;
                             player[Score] += 100;
+                            map.events.push(GameEvent::Score { x, y, amount: 100 });
                             player.paused = true;
                             player.upgrading = Some(Upgrading::new(player, state));
 
@@ -138,16 +145,16 @@ pub fn check_bullets(map: &mut Map, player: &mut Player, state: &mut State, fram
             }
         }
 
+        let (dx, dy, dmg) = (bullet.dx, bullet.dy, bullet.dmg);
         match map.tiles[(x, y)] {
-            Tile::Wall(Wall { ref mut bullets }) => {
-                *bullets += 1;
-
-                map.bullets.swap_remove(i);
-                continue;
-            }
-            Tile::MovableWall(MovableWall { ref mut bullets }) => {
-                *bullets += 1;
-
+            // whatever stops a bullet takes its damage
+            Tile::Wall(_)
+            | Tile::MovableWall(_)
+            | Tile::Door(Door { open: false, .. })
+            | Tile::Barricade(_) => {
+                map.chip(x, y, dmg);
+
+                map.events.push(GameEvent::Impact { x, y, dx, dy });
                 map.bullets.swap_remove(i);
                 continue;
             }
@@ -3,10 +3,64 @@ use super::*;
 pub struct Camera {
     pub x: f32,
     pub y: f32,
+    // position at the previous tick
+    pub px: f32,
+    pub py: f32,
 }
 
 impl Camera {
     pub fn new(x: f32, y: f32) -> Self {
-        Camera { x, y }
+        Camera { x, y, px: x, py: y }
     }
+    pub fn follow(&mut self, x: f32, y: f32) {
+        self.px = self.x;
+        self.py = self.y;
+        self.x += (x - self.x) * 0.05;
+        self.y += (y - self.y) * 0.05;
+    }
+    // position between the last two ticks for rendering
+    pub fn lerp(&self, alpha: f32) -> Camera {
+        let (x, y) = (
+            self.px + (self.x - self.px) * alpha,
+            self.py + (self.y - self.py) * alpha,
+        );
+        Camera { x, y, px: x, py: y }
+    }
+}
+
+pub const ZOOMS: [f32; 6] = [0.5, 0.75, 1.0, 1.25, 1.5, 2.0];
+
+// tiles visible from the center to the top edge at 1080p and zoom 1
+const BASE_VFOV: f32 = 1080.0 / 48.0 * 0.5;
+
+// what part of the map fits on the screen
+pub struct View {
+    pub tile: f32,
+    pub hfov: f32,
+    pub vfov: f32,
+    // how fast the player's light fades with squared distance
+    pub falloff: f32,
+}
+
+impl View {
+    pub fn new(state: &State) -> Self {
+        let tile = (48.0 * state.scale() * state.storage.display.zoom).round();
+        let (hfov, vfov) = (state.w / tile * 0.5, state.h / tile * 0.5);
+
+        View {
+            tile,
+            hfov: hfov.ceil() + 1.0,
+            vfov: vfov.ceil() + 1.0,
+            falloff: 2.0 * (BASE_VFOV / vfov).powi(2),
+        }
+    }
+}
+
+// steps through `ZOOMS`, returns the new zoom
+pub fn zoom_step(zoom: f32, d: i32) -> f32 {
+    let idx = ZOOMS
+        .iter()
+        .position(|&z| z == zoom)
+        .unwrap_or(ZOOMS.len() / 2) as i32;
+    ZOOMS[(idx + d).clamp(0, ZOOMS.len() as i32 - 1) as usize]
 }
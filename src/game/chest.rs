@@ -34,6 +34,33 @@ impl From<i32> for ChestType {
 }
 
 impl ChestType {
+    pub fn color(&self, theme: &Theme, frame: i32) -> Color {
+        match self {
+            ChestType::Ammo => theme.ammo,
+            ChestType::Bomb => theme.bombs,
+            ChestType::Turret => theme.turrets,
+            ChestType::Emp => theme.emps,
+            ChestType::Food => theme.food,
+            ChestType::Rainbow => [
+                theme.ammo,
+                theme.bombs,
+                theme.turrets,
+                theme.emps,
+                theme.food,
+            ][((frame / 30) % 5) as usize],
+        }
+    }
+    // shape cue drawn on the chest
+    pub fn letter(&self) -> &'static str {
+        match self {
+            ChestType::Ammo => "A",
+            ChestType::Bomb => "B",
+            ChestType::Turret => "T",
+            ChestType::Emp => "E",
+            ChestType::Food => "F",
+            ChestType::Rainbow => "*",
+        }
+    }
     pub fn random() -> Self {
         pub const COUNT: i32 = 5;
 
@@ -44,24 +71,20 @@ impl ChestType {
 
 const FOV: i32 = 12;
 pub fn spawn_random_chest(map: &mut Map, player: &Player, frame: i32) {
-    let mut rng = thread_rng();
-
-    loop {
-        let x = rng.gen_range(0..map.w);
-        let y = rng.gen_range(0..map.h);
-
-        if !map.pass[(x, y)] || dist(x, y, player.x, player.y) < FOV + 4 {
-            continue;
-        }
-
-        let ty = ChestType::from(rng.gen_range(0..5));
-        spawn_chest(map, frame, x, y, ty);
+    let Some((x, y)) = map.spawn_point(&map.points.chests, (player.x, player.y), FOV + 4) else {
+        return;
+    };
 
-        break;
-    }
+    let ty = ChestType::from(thread_rng().gen_range(0..5));
+    spawn_chest(map, frame, x, y, ty);
 }
 
 pub fn spawn_chest(map: &mut Map, frame: i32, x: i32, y: i32, ty: ChestType) {
+    // picking it up leaves bare floor, so water and spikes don't get chests dropped on them
+    if !matches!(map.tiles[(x, y)], Tile::None) {
+        return;
+    }
+
     let chest = Chest {
         start: frame,
         duration: 14_400,
@@ -0,0 +1,93 @@
+use super::*;
+
+// tiles per chunk side, a power of two so lookups are shifts and masks
+pub const CHUNK: i32 = 64;
+const SHIFT: i32 = CHUNK.trailing_zeros() as i32;
+const MASK: i32 = CHUNK - 1;
+
+// a `w * h` grid stored in square chunks that are only allocated once written,
+// reads of chunks that aren't there (or anything off the grid) give `fill`
+pub struct Chunks<T> {
+    pub w: i32,
+    pub h: i32,
+    // chunks per row
+    cw: i32,
+    slots: Vec<Option<Box<[T]>>>,
+    fill: T,
+}
+
+impl<T: Clone> Chunks<T> {
+    pub fn new(w: i32, h: i32, fill: T) -> Self {
+        let (cw, ch) = ((w + MASK) >> SHIFT, (h + MASK) >> SHIFT);
+        Chunks {
+            w,
+            h,
+            cw,
+            slots: (0..cw * ch).map(|_| None).collect(),
+            fill,
+        }
+    }
+    // splits a dense row major grid into chunks
+    pub fn from_vec(w: i32, h: i32, inner: Vec<T>, fill: T) -> Self {
+        let mut chunks = Chunks::new(w, h, fill);
+        for (i, value) in inner.into_iter().enumerate() {
+            let i = i as i32;
+            chunks[(i % w, i / w)] = value;
+        }
+        chunks
+    }
+    // the chunk a tile falls in
+    pub fn chunk_of(x: i32, y: i32) -> (i32, i32) {
+        (x >> SHIFT, y >> SHIFT)
+    }
+    fn slot(&self, (cx, cy): (i32, i32)) -> Option<usize> {
+        let ch = self.slots.len() as i32 / self.cw.max(1);
+        (cx >= 0 && cy >= 0 && cx < self.cw && cy < ch).then(|| (cx + cy * self.cw) as usize)
+    }
+    pub fn loaded(&self, chunk: (i32, i32)) -> bool {
+        self.slot(chunk).is_some_and(|k| self.slots[k].is_some())
+    }
+    // frees a chunk, reads go back to `fill`
+    pub fn unload(&mut self, chunk: (i32, i32)) -> Option<Box<[T]>> {
+        let k = self.slot(chunk)?;
+        self.slots[k].take()
+    }
+    // every value in allocated chunks, including the padding past the edge of the grid
+    pub fn values(&self) -> impl Iterator<Item = &T> {
+        self.slots.iter().flatten().flat_map(|chunk| chunk.iter())
+    }
+    pub fn values_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        self.slots
+            .iter_mut()
+            .flatten()
+            .flat_map(|chunk| chunk.iter_mut())
+    }
+}
+
+impl<T> Index<(i32, i32)> for Chunks<T> {
+    type Output = T;
+    fn index(&self, (x, y): (i32, i32)) -> &Self::Output {
+        if x < 0 || y < 0 || x >= self.w || y >= self.h {
+            return &self.fill;
+        }
+        match self.slots[((x >> SHIFT) + (y >> SHIFT) * self.cw) as usize] {
+            Some(ref chunk) => &chunk[((x & MASK) + (y & MASK) * CHUNK) as usize],
+            None => &self.fill,
+        }
+    }
+}
+
+impl<T: Clone> IndexMut<(i32, i32)> for Chunks<T> {
+    fn index_mut(&mut self, (x, y): (i32, i32)) -> &mut Self::Output {
+        assert!(
+            x >= 0 && y >= 0 && x < self.w && y < self.h,
+            "({}, {}) is off the grid",
+            x,
+            y
+        );
+        let fill = &self.fill;
+        let chunk = self.slots[((x >> SHIFT) + (y >> SHIFT) * self.cw) as usize]
+            .get_or_insert_with(|| vec![fill.clone(); (CHUNK * CHUNK) as usize].into_boxed_slice());
+        &mut chunk[((x & MASK) + (y & MASK) * CHUNK) as usize]
+    }
+}
@@ -0,0 +1,265 @@
+use super::*;
+
+// something that happened during a tick, only read by cosmetic effects
+#[derive(Clone)]
+pub enum GameEvent {
+    Explosion {
+        x: i32,
+        y: i32,
+        radius: i32,
+    },
+    Death {
+        x: i32,
+        y: i32,
+        boss: bool,
+    },
+    // what the chest actually added to each supply that went up, food in percent of
+    // the bar, a rainbow chest lists every one it topped up
+    Pickup {
+        x: i32,
+        y: i32,
+        ty: ChestType,
+        gained: Vec<(ChestType, i32)>,
+    },
+    Impact {
+        x: i32,
+        y: i32,
+        dx: i32,
+        dy: i32,
+    },
+    Damage {
+        x: i32,
+        y: i32,
+        amount: i32,
+    },
+    Score {
+        x: i32,
+        y: i32,
+        amount: i32,
+    },
+}
+
+const MAX_PARTICLES: usize = 2048;
+
+// largest camera offset in tiles at full trauma
+const MAX_SHAKE: f32 = 0.5;
+
+struct Particle {
+    x: f32,
+    y: f32,
+    vx: f32,
+    vy: f32,
+    // fraction of the velocity kept every tick
+    drag: f32,
+    size: f32,
+    grow: f32,
+    color: Color,
+    age: i32,
+    life: i32,
+}
+
+struct Burst {
+    count: usize,
+    speed: (f32, f32),
+    life: (i32, i32),
+    size: f32,
+    grow: f32,
+    drag: f32,
+    color: Color,
+}
+
+// particles and camera shake, they have their own rng and never feed back into the game
+pub struct Effects {
+    particles: VecDeque<Particle>,
+    trauma: f32,
+    pub shake: (f32, f32),
+    rng: rngs::StdRng,
+}
+
+impl Effects {
+    pub fn new() -> Self {
+        Effects {
+            particles: VecDeque::with_capacity(MAX_PARTICLES),
+            trauma: 0.0,
+            shake: (0.0, 0.0),
+            rng: rngs::StdRng::from_entropy(),
+        }
+    }
+    pub fn handle(&mut self, event: &GameEvent, (px, py): (i32, i32), state: &State) {
+        let display = &state.storage.display;
+        let (particles, shake) = (display.particles, display.shake);
+
+        match *event {
+            GameEvent::Explosion { x, y, radius } => {
+                if particles {
+                    let at = (x as f32, y as f32);
+                    let speed = radius as f32 / 40.0;
+                    self.burst(
+                        at,
+                        Burst {
+                            count: 24,
+                            speed: (speed * 0.5, speed * 1.5),
+                            life: (20, 40),
+                            size: 0.15,
+                            grow: 0.0,
+                            drag: 0.92,
+                            color: [255, 200, 96],
+                        },
+                    );
+                    self.burst(
+                        at,
+                        Burst {
+                            count: 16,
+                            speed: (speed * 0.3, speed),
+                            life: (15, 30),
+                            size: 0.25,
+                            grow: 0.0,
+                            drag: 0.85,
+                            color: state.theme.wall.shade(0.6),
+                        },
+                    );
+                    self.burst(
+                        at,
+                        Burst {
+                            count: 12,
+                            speed: (0.0, speed * 0.3),
+                            life: (60, 100),
+                            size: 0.4,
+                            grow: 0.015,
+                            drag: 0.95,
+                            color: [80, 80, 80],
+                        },
+                    );
+                }
+                if shake {
+                    let d = dist(x, y, px, py) as f32;
+                    self.trauma += 0.8 * 100.0 / (100.0 + d * d);
+                }
+            }
+            GameEvent::Death { x, y, boss } => {
+                if particles {
+                    self.burst(
+                        (x as f32, y as f32),
+                        Burst {
+                            count: if boss { 48 } else { 10 },
+                            speed: (0.02, if boss { 0.2 } else { 0.1 }),
+                            life: (30, 60),
+                            size: 0.2,
+                            grow: 0.0,
+                            drag: 0.85,
+                            color: [120, 0, 0],
+                        },
+                    );
+                }
+                if shake && boss {
+                    self.trauma += 0.5;
+                }
+            }
+            GameEvent::Pickup { x, y, ref ty, .. } => {
+                if particles {
+                    self.burst(
+                        (x as f32, y as f32),
+                        Burst {
+                            count: 12,
+                            speed: (0.03, 0.08),
+                            life: (20, 40),
+                            size: 0.12,
+                            grow: 0.0,
+                            drag: 0.9,
+                            color: ty.color(&state.theme, 0),
+                        },
+                    );
+                }
+            }
+            GameEvent::Impact { x, y, dx, dy } => {
+                if particles {
+                    // sparks bounce back from the wall
+                    let at = (x as f32 - dx as f32 * 0.5, y as f32 - dy as f32 * 0.5);
+                    for _ in 0..6 {
+                        let angle = (-dy as f32).atan2(-dx as f32) + self.rng.gen_range(-1.0..1.0);
+                        let speed = self.rng.gen_range(0.05..0.15);
+                        let life = self.rng.gen_range(8..16);
+                        self.push(Particle {
+                            x: at.0,
+                            y: at.1,
+                            vx: angle.cos() * speed,
+                            vy: angle.sin() * speed,
+                            drag: 0.85,
+                            size: 0.08,
+                            grow: 0.0,
+                            color: [255, 220, 128],
+                            age: 0,
+                            life,
+                        });
+                    }
+                }
+            }
+            GameEvent::Damage { .. } | GameEvent::Score { .. } => {}
+        }
+
+        self.trauma = self.trauma.min(1.0);
+    }
+    fn burst(&mut self, (x, y): (f32, f32), burst: Burst) {
+        for _ in 0..burst.count {
+            let angle = self.rng.gen_range(-PI..PI);
+            let speed = self.rng.gen_range(burst.speed.0..=burst.speed.1);
+            let size = burst.size * self.rng.gen_range(0.7..1.3);
+            let life = self.rng.gen_range(burst.life.0..=burst.life.1);
+            self.push(Particle {
+                x,
+                y,
+                vx: angle.cos() * speed,
+                vy: angle.sin() * speed,
+                drag: burst.drag,
+                size,
+                grow: burst.grow,
+                color: burst.color,
+                age: 0,
+                life,
+            });
+        }
+    }
+    fn push(&mut self, particle: Particle) {
+        // the oldest particles make room when there are too many
+        if self.particles.len() >= MAX_PARTICLES {
+            self.particles.pop_front();
+        }
+        self.particles.push_back(particle);
+    }
+    pub fn update(&mut self) {
+        self.particles.retain_mut(|p| {
+            p.x += p.vx;
+            p.y += p.vy;
+            p.vx *= p.drag;
+            p.vy *= p.drag;
+            p.size += p.grow;
+            p.age += 1;
+            p.age < p.life
+        });
+
+        let offset = MAX_SHAKE * self.trauma * self.trauma;
+        self.shake = (
+            offset * self.rng.gen_range(-1.0..=1.0),
+            offset * self.rng.gen_range(-1.0..=1.0),
+        );
+        self.trauma = (self.trauma - 0.02).max(0.0);
+    }
+    pub fn render(&self, draw: &Draw, fog: &Fog, cam: &Camera, tile: f32) {
+        for p in &self.particles {
+            let (x, y) = (p.x.round() as i32, p.y.round() as i32);
+            if x < 0 || y < 0 || !fog.visible(x, y) {
+                continue;
+            }
+
+            let [r, g, b] = p.color;
+            let alpha = 255.0 * (1.0 - p.age as f32 / p.life as f32);
+            draw.rect()
+                .x_y(
+                    ((p.x - cam.x) * tile).round(),
+                    ((cam.y - p.y) * tile).round(),
+                )
+                .w_h((p.size * tile).ceil(), (p.size * tile).ceil())
+                .rgba8(r, g, b, alpha as u8);
+        }
+    }
+}
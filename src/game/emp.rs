@@ -55,16 +55,31 @@ pub fn check_emp(player: &mut Player, map: &mut Map, state: &mut State, frame: i
                 };
                 enemy.immobilized = emp.immobilize;
                 enemy.hp -= emp.damage as i8;
+                map.events.push(GameEvent::Damage {
+                    x,
+                    y,
+                    amount: emp.damage as i32,
+                });
 
                 if enemy.hp <= 0 {
                     player.progress.killed += 1;
 
                     map.pass[(x, y)] = true;
+                    map.events.push(GameEvent::Death {
+                        x,
+                        y,
+                        boss: matches!(enemy.ty, EnemyType::ZombieBoss(_)),
+                    });
 
                     let mut rng = thread_rng();
                     match enemy.ty {
                         EnemyType::Zombie => {
                             player[Score] += player[ScoreEmp];
+                            map.events.push(GameEvent::Score {
+                                x,
+                                y,
+                                amount: player[ScoreEmp],
+                            });
 
                             if rng.gen_range(0..100) < 65 + player[Luck] {
                                 spawn_chest(map, frame, x, y, ChestType::random());
@@ -73,6 +88,7 @@ pub fn check_emp(player: &mut Player, map: &mut Map, state: &mut State, frame: i
                         EnemyType::ZombieBoss(_) => {
                             map.boss.pos = None;
                             player[Score] += 100;
+                            map.events.push(GameEvent::Score { x, y, amount: 100 });
                             player.paused = true;
                             player.upgrading = Some(Upgrading::new(player, state));
 
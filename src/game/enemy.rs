@@ -32,70 +32,68 @@ pub struct ZombieBoss {
 }
 
 const FOV: f32 = 24.0;
+// wall hp a boss knocks off per hit
+const BOSS_SMASH: i8 = 4;
 pub fn spawn_enemy(map: &mut Map, player: &Player) {
-    let mut rng = thread_rng();
+    let Some((x, y)) = map.spawn_point(&map.points.zones, (player.x, player.y), FOV as i32 + 4)
+    else {
+        return;
+    };
 
-    loop {
-        let x = rng.gen_range(0..map.w);
-        let y = rng.gen_range(0..map.h);
+    let hp = 1 + (player[Score] / 1000) as i8;
 
-        if !map.pass[(x, y)] || dist(x, y, player.x, player.y) < FOV as i32 + 4 {
-            continue;
-        }
-
-        let hp = 1 + (player[Score] / 1000) as i8;
-
-        map.pass[(x, y)] = false;
-        map.enemies[(x, y)] = Some(Enemy {
-            uid: map.spawned,
-            hp,
-            mhp: hp,
-            last: 0,
-            slowed: 0,
-            immobilized: false,
-            cd: 19,
-            ty: EnemyType::Zombie,
-        });
-        map.spawned += 1;
-        break;
-    }
+    map.pass[(x, y)] = false;
+    map.enemies[(x, y)] = Some(Enemy {
+        uid: map.spawned,
+        hp,
+        mhp: hp,
+        last: 0,
+        slowed: 0,
+        immobilized: false,
+        cd: 19,
+        ty: EnemyType::Zombie,
+    });
+    map.spawned += 1;
 }
 
-pub fn spawn_boss(map: &mut Map, player: &Player, state: &mut State, frame: i32, hp: i8) -> (i32, i32) {
-    let mut rng = thread_rng();
+pub fn spawn_boss(
+    map: &mut Map,
+    player: &Player,
+    state: &mut State,
+    frame: i32,
+    hp: i8,
+) -> Option<(i32, i32)> {
+    let (x, y) = map.spawn_point(&map.points.bosses, (player.x, player.y), FOV as i32 + 1)?;
 
-    loop {
-        let x = rng.gen_range(1..map.w - 1);
-        let y = rng.gen_range(1..map.h - 1);
+    state.play(Sound::BossAppear, 0.5);
 
-        if !map.pass[(x, y)] || dist(x, y, player.x, player.y) <= FOV as i32 {
-            continue;
-        }
-
-        state.play(Sound::BossAppear, 0.5);
-
-        let boss = Enemy {
-            uid: map.spawned,
-            hp,
-            mhp: hp,
-            last: 0,
-            cd: 47,
-            slowed: 0,
-            immobilized: false,
-            ty: EnemyType::ZombieBoss(ZombieBoss {
-                last: frame,
-                cd: 6 * 60,
-            }),
-        };
+    let boss = Enemy {
+        uid: map.spawned,
+        hp,
+        mhp: hp,
+        last: 0,
+        cd: 47,
+        slowed: 0,
+        immobilized: false,
+        ty: EnemyType::ZombieBoss(ZombieBoss {
+            last: frame,
+            cd: 6 * 60,
+        }),
+    };
 
-        map.pass[(x, y)] = false;
-        map.enemies[(x, y)] = Some(boss);
-        map.spawned += 1;
-        return (x, y);
-    }
+    map.pass[(x, y)] = false;
+    map.enemies[(x, y)] = Some(boss);
+    map.spawned += 1;
+    Some((x, y))
 }
 
-pub fn update_enemies(map: &mut Map, player: &mut Player, state: &mut State, frame: i32) {
+pub fn update_enemies(
+    map: &mut Map,
+    flow: &FlowField,
+    player: &mut Player,
+    state: &mut State,
+    frame: i32,
+) {
     for x in max(0.0, player.x as f32 - MOB_FOV as f32)
         ..min(player.x as f32 + MOB_FOV as f32, map.w as f32)
     {
@@ -135,6 +133,7 @@ pub fn update_enemies(map: &mut Map, player: &mut Player, state: &mut State, fra
                     }
                 }
 
+                let wading = matches!(map.tiles[(x, y)], Tile::Water);
                 let enemy = map.enemies[(x, y)].as_mut().unwrap();
                 if (enemy.immobilized && enemy.slowed > frame)
                     || enemy.last
@@ -142,7 +141,7 @@ pub fn update_enemies(map: &mut Map, player: &mut Player, state: &mut State, fra
                             enemy.cd * 3
                         } else {
                             enemy.cd
-                        }
+                        } * if wading { 2 } else { 1 }
                         > frame
                 {
                     continue;
@@ -150,16 +149,38 @@ pub fn update_enemies(map: &mut Map, player: &mut Player, state: &mut State, fra
                 enemy.last = frame;
                 let is_boss = matches!(enemy.ty, EnemyType::ZombieBoss(_));
 
+                // doors and barricades on the way get beaten on, bosses with no way
+                // towards the player smash through the wall facing them
+                let step = flow.step(map, (x, y));
+                let d = dist(player.x, player.y, x, y);
+                let smash = DIRECTIONS
+                    .iter()
+                    .map(|&(dx, dy)| (x + dx, y + dy))
+                    .filter(|_| is_boss && step.is_none())
+                    .find(|&p| {
+                        matches!(map.tiles[p], Tile::Wall(_) | Tile::MovableWall(_))
+                            && dist(player.x, player.y, p.0, p.1) < d
+                    });
+                if let Some(at) = step.filter(|&p| map.tiles[p].breakable()).or(smash) {
+                    bash(map, (x, y), at, if is_boss { BOSS_SMASH } else { 1 });
+                    continue;
+                }
+
                 let mut rng = thread_rng();
                 let (nx, ny) = if rng.gen_range(0..100) < 70 {
-                    pathfind(map, (x, y), (player.x, player.y))
+                    // standing still when there's no way
+                    step.unwrap_or((x, y))
                 } else {
                     let dx = rng.gen_range(-1..=1);
                     let dy = if dx == 0 { rng.gen_range(-1..=1) } else { 0 };
 
                     (x + dx, y + dy)
                 };
-                if nx != 0 && ny != 0 && map.pass[(nx, ny)] {
+                // walls only get shoved on the way to the player, not while wandering
+                if step == Some((nx, ny)) {
+                    shove(map, (x, y), (nx, ny), (player.x, player.y));
+                }
+                if map.pass[(nx, ny)] {
                     map.pass[(x, y)] = true;
                     map.pass[(nx, ny)] = false;
                     map.enemies[(nx, ny)] = map.enemies[(x, y)].take();
@@ -170,9 +191,165 @@ pub fn update_enemies(map: &mut Map, player: &mut Player, state: &mut State, fra
 
                     if is_boss {
                         map.boss.pos = Some(Position { x: nx, y: ny });
+                    } else if matches!(map.tiles[(nx, ny)], Tile::Spikes) {
+                        spiked(map, (nx, ny));
                     }
                 }
             }
         }
     }
 }
+
+// the zombie at `from` pushes the movable wall at `to` one further if it can, the wall
+// keeps its wear and the zombie is a step slower after
+fn shove(map: &mut Map, from: (i32, i32), to: (i32, i32), player: (i32, i32)) -> bool {
+    if !can_push(map, from, to, player) {
+        return false;
+    }
+    let behind = (2 * to.0 - from.0, 2 * to.1 - from.1);
+    map.tiles[behind] = std::mem::replace(&mut map.tiles[to], Tile::None);
+    map.pass[behind] = false;
+    map.pass[to] = true;
+
+    if let Some(ref mut enemy) = map.enemies[from] {
+        enemy.last += enemy.cd;
+    }
+    true
+}
+
+// a hit on whatever is in the way at `(x, y)` from the zombie at `from`
+fn bash(map: &mut Map, from: (i32, i32), (x, y): (i32, i32), dmg: i8) {
+    map.chip(x, y, dmg);
+    map.events.push(GameEvent::Impact {
+        x,
+        y,
+        dx: x - from.0,
+        dy: y - from.1,
+    });
+}
+
+// the zombie that just stepped onto the spikes at `(x, y)` takes a hit, bosses walk right over
+// them. traps aren't the player's kills, they don't count towards unlocks or score
+fn spiked(map: &mut Map, (x, y): (i32, i32)) {
+    let Some(ref mut enemy) = map.enemies[(x, y)] else {
+        return;
+    };
+    enemy.hp -= 1;
+    map.events.push(GameEvent::Damage { x, y, amount: 1 });
+
+    if enemy.hp <= 0 {
+        map.events.push(GameEvent::Death { x, y, boss: false });
+
+        map.enemies[(x, y)].take();
+        map.pass[(x, y)] = true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn map(src: &str) -> Map {
+        Map::new(parse_map(src).unwrap())
+    }
+
+    // a zombie that last moved on `last`
+    fn zombie(map: &mut Map, (x, y): (i32, i32), last: i32) {
+        map.pass[(x, y)] = false;
+        map.enemies[(x, y)] = Some(Enemy {
+            uid: map.spawned,
+            hp: 1,
+            mhp: 1,
+            last,
+            cd: 19,
+            slowed: 0,
+            immobilized: false,
+            ty: EnemyType::Zombie,
+        });
+        map.spawned += 1;
+    }
+
+    fn last(map: &Map, at: (i32, i32)) -> i32 {
+        map.enemies[at].as_ref().unwrap().last
+    }
+
+    #[test]
+    fn shoving_slides_the_wall_and_costs_a_step() {
+        let mut map = map("#######\n\
+                           #@..+.#\n\
+                           #######\n");
+        map.chip(4, 1, 2);
+        zombie(&mut map, (5, 1), 100);
+        let player = map.start;
+
+        assert!(shove(&mut map, (5, 1), (4, 1), player));
+        // it keeps its wear
+        assert!(matches!(
+            map.tiles[(3, 1)],
+            Tile::MovableWall(ref wall) if wall.hp == MOVABLE_WALL_HP - 2
+        ));
+        assert!(matches!(map.tiles[(4, 1)], Tile::None));
+        assert!(!map.pass[(3, 1)]);
+        assert!(map.pass[(4, 1)]);
+        assert_eq!(last(&map, (5, 1)), 119);
+    }
+
+    #[test]
+    fn walls_are_not_shoved_onto_the_player() {
+        let mut map = map("######\n\
+                           #@+..#\n\
+                           ######\n");
+        zombie(&mut map, (3, 1), 100);
+        let player = map.start;
+
+        assert!(!shove(&mut map, (3, 1), (2, 1), player));
+        assert!(matches!(map.tiles[(2, 1)], Tile::MovableWall(_)));
+        assert_eq!(last(&map, (3, 1)), 100);
+    }
+
+    #[test]
+    fn walls_only_slide_over_bare_floor() {
+        let mut map = map("########\n\
+                           #@.....#\n\
+                           #..+...#\n\
+                           #..+...#\n\
+                           #.^+...#\n\
+                           ########\n");
+        // a chest, another zombie and spikes behind the walls
+        spawn_chest(&mut map, 0, 2, 2, ChestType::Ammo);
+        zombie(&mut map, (2, 3), 100);
+        for y in 2..5 {
+            zombie(&mut map, (4, y), 100);
+        }
+
+        let player = map.start;
+        for y in 2..5 {
+            assert!(!shove(&mut map, (4, y), (3, y), player));
+            assert!(matches!(map.tiles[(3, y)], Tile::MovableWall(_)));
+            assert_eq!(last(&map, (4, y)), 100);
+        }
+    }
+
+    #[test]
+    fn walls_are_not_shoved_out_of_the_loaded_chunks() {
+        // the wall sits on the last column of the first chunk, the floor behind it gets put away
+        let w = CHUNK as usize + 4;
+        let wall = "#".repeat(w);
+        let mut row = vec!['.'; w];
+        (row[0], row[1], row[w - 1]) = ('#', '@', '#');
+        row[CHUNK as usize - 1] = '+';
+        let row: String = row.into_iter().collect();
+        let mut map = map(&format!("{wall}\n{row}\n{wall}\n"));
+
+        let (from, to) = ((CHUNK - 2, 1), (CHUNK - 1, 1));
+        zombie(&mut map, from, 100);
+        let player = map.start;
+        assert!(can_push(&map, from, to, player));
+
+        map.tiles.unload((1, 0));
+        map.pass.unload((1, 0));
+        assert!(!shove(&mut map, from, to, player));
+        assert!(matches!(map.tiles[to], Tile::MovableWall(_)));
+        assert_eq!(last(&map, from), 100);
+    }
+}
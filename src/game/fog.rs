@@ -0,0 +1,45 @@
+use super::*;
+
+// how far the player can see when nothing is in the way
+pub const SIGHT: i32 = 24;
+
+// brightness of tiles that were seen lit before but aren't now, light
+// dimmer than that doesn't show enough of a tile to remember it
+pub const MEMORY: f32 = 0.12;
+
+pub struct Fog {
+    // frame a tile was last in line of sight, -1 if never
+    seen: Chunks<i32>,
+    // tiles that were in sight while lit, the dark ones stay unknown
+    lit: Chunks<bool>,
+    now: i32,
+}
+
+impl Fog {
+    pub fn new(w: i32, h: i32) -> Self {
+        Fog {
+            seen: Chunks::new(w, h, -1),
+            lit: Chunks::new(w, h, false),
+            now: 0,
+        }
+    }
+    pub fn update(&mut self, map: &Map, (x, y): (i32, i32), frame: i32, lights: &LightMap) {
+        self.now = frame;
+
+        let (seen, lit) = (&mut self.seen, &mut self.lit);
+        shadowcast(map, (x, y), SIGHT, |x, y| {
+            if x >= 0 && y >= 0 && x < map.w && y < map.h {
+                seen[(x, y)] = frame;
+                if lights.at(x, y).iter().any(|&c| c >= MEMORY) {
+                    lit[(x, y)] = true;
+                }
+            }
+        });
+    }
+    pub fn visible(&self, x: i32, y: i32) -> bool {
+        self.seen[(x, y)] == self.now
+    }
+    pub fn explored(&self, x: i32, y: i32) -> bool {
+        self.lit[(x, y)]
+    }
+}
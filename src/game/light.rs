@@ -0,0 +1,254 @@
+use super::*;
+
+pub struct Light {
+    pub x: f32,
+    pub y: f32,
+    pub radius: f32,
+    pub color: [f32; 3],
+    pub intensity: f32,
+}
+
+impl Light {
+    pub fn new(x: f32, y: f32, radius: f32, color: Rgb8, intensity: f32) -> Self {
+        Light {
+            x,
+            y,
+            radius,
+            color: [
+                color.red as f32 / 255.0,
+                color.green as f32 / 255.0,
+                color.blue as f32 / 255.0,
+            ],
+            intensity,
+        }
+    }
+    // every light source on the map for the current frame
+    pub fn collect(
+        map: &Map,
+        theme: &Theme,
+        frame: i32,
+        (cx, cy): (f32, f32),
+        view: &View,
+    ) -> Vec<Light> {
+        let mut lights = vec![];
+
+        // player light fades in at the start of the game
+        let mut radius = (255.0 / view.falloff).sqrt();
+        if frame < 60 {
+            radius *= ((frame - 30) as f32 / 30.0).clamp(0.001, 1.0).sqrt();
+        }
+        lights.push(Light::new(cx, cy, radius, WHITE, 1.0));
+
+        for bomb in &map.bombs {
+            let (x, y) = (bomb.x as f32, bomb.y as f32);
+            if frame < bomb.start + bomb.duration - 20 {
+                lights.push(Light::new(x, y, 4.0, rgb8(255, 96, 64), 1.0));
+            } else {
+                let t = (frame - (bomb.start + bomb.duration - 20)) as f32 / 20.0;
+                let radius = 4.0 / (1.0 - 0.5 * (PI * t).sin()).sqrt();
+                lights.push(Light::new(x, y, radius, rgb8(255, 200, 96), 1.0));
+            }
+        }
+
+        for emp in &map.emps {
+            let q = (PI * (frame - emp.start) as f32 / emp.duration as f32).sin();
+            let (x, y) = (emp.x as f32, emp.y as f32);
+            lights.push(Light::new(
+                x,
+                y,
+                emp.radius as f32 + 1.0,
+                rgb8(0x89, 0xD1, 0xFE),
+                q,
+            ));
+        }
+
+        for turret in &map.turrets {
+            let p = (frame - turret.last) as f32 / turret.cd as f32;
+            let charge = ((p - 0.5) * 2.0).clamp(0.0, 1.0);
+            let color = theme.turret.mix(theme.turret_charged, charge).rgb();
+            lights.push(Light::new(
+                turret.x as f32,
+                turret.y as f32,
+                2.5,
+                color,
+                0.6,
+            ));
+        }
+
+        for &(x, y) in &map.chests {
+            if let Tile::Chest(ref chest) = map.tiles[(x, y)] {
+                let c = chest.ty.color(theme, frame).rgb();
+                lights.push(Light::new(x as f32, y as f32, 2.0, c, 0.4));
+            }
+        }
+
+        // muzzle flash for freshly fired bullets
+        for bullet in &map.bullets {
+            if frame - bullet.start < 6 {
+                let (x, y) = (bullet.x as f32, bullet.y as f32);
+                lights.push(Light::new(x, y, 3.0, rgb8(255, 220, 128), 0.8));
+            }
+        }
+
+        lights
+    }
+}
+
+// accumulated light of every tile in a rectangle of the map
+pub struct LightMap {
+    x: i32,
+    y: i32,
+    w: i32,
+    h: i32,
+    cells: Vec<[f32; 3]>,
+    // last light that touched a cell so octant edges aren't lit twice
+    stamp: Vec<u32>,
+}
+
+impl LightMap {
+    pub fn build(map: &Map, lights: &[Light], [x0, y0, x1, y1]: [i32; 4]) -> Self {
+        let (w, h) = (x1 - x0 + 1, y1 - y0 + 1);
+        let mut lm = LightMap {
+            x: x0,
+            y: y0,
+            w,
+            h,
+            cells: vec![[0.0; 3]; (w * h) as usize],
+            stamp: vec![u32::MAX; (w * h) as usize],
+        };
+
+        for (k, light) in lights.iter().enumerate() {
+            let r = light.radius.ceil() as i32;
+            let (ox, oy) = (light.x.round() as i32, light.y.round() as i32);
+
+            if light.intensity <= 0.0 || ox + r < x0 || ox - r > x1 || oy + r < y0 || oy - r > y1 {
+                continue;
+            }
+
+            shadowcast(map, (ox, oy), r, |x, y| {
+                if x < x0 || x > x1 || y < y0 || y > y1 {
+                    return;
+                }
+                let idx = ((x - x0) + (y - y0) * w) as usize;
+                if lm.stamp[idx] == k as u32 {
+                    return;
+                }
+                lm.stamp[idx] = k as u32;
+
+                let dsq = (x as f32 - light.x).powi(2) + (y as f32 - light.y).powi(2);
+                let level = light.intensity * (1.0 - dsq / (light.radius * light.radius));
+                if level > 0.0 {
+                    for c in 0..3 {
+                        lm.cells[idx][c] += light.color[c] * level;
+                    }
+                }
+            });
+        }
+
+        lm
+    }
+    pub fn at(&self, x: i32, y: i32) -> [f32; 3] {
+        if x < self.x || x >= self.x + self.w || y < self.y || y >= self.y + self.h {
+            return [0.0; 3];
+        }
+        let [r, g, b] = self.cells[((x - self.x) + (y - self.y) * self.w) as usize];
+        [r.min(1.0), g.min(1.0), b.min(1.0)]
+    }
+}
+
+// tints a color by the light falling on it
+pub fn lit([r, g, b]: Color, [lr, lg, lb]: [f32; 3]) -> Rgba8 {
+    Rgba8::new(
+        (r as f32 * lr) as u8,
+        (g as f32 * lg) as u8,
+        (b as f32 * lb) as u8,
+        255,
+    )
+}
+
+// octant transforms for shadowcasting
+const OCTANTS: [[i32; 4]; 8] = [
+    [1, 0, 0, 1],
+    [0, 1, 1, 0],
+    [0, -1, 1, 0],
+    [-1, 0, 0, 1],
+    [-1, 0, 0, -1],
+    [0, -1, -1, 0],
+    [0, 1, -1, 0],
+    [1, 0, 0, -1],
+];
+
+// calls `f` for every tile visible from `origin` within `radius`, walls are visible but block
+pub fn shadowcast(map: &Map, origin: (i32, i32), radius: i32, mut f: impl FnMut(i32, i32)) {
+    f(origin.0, origin.1);
+    for oct in OCTANTS {
+        cast(map, origin, 1, 1.0, 0.0, radius, oct, &mut f);
+    }
+}
+
+fn opaque(map: &Map, x: i32, y: i32) -> bool {
+    x < 0 || y < 0 || x >= map.w || y >= map.h || map.tiles[(x, y)].opaque()
+}
+
+fn cast(
+    map: &Map,
+    (cx, cy): (i32, i32),
+    row: i32,
+    mut start: f32,
+    end: f32,
+    radius: i32,
+    [xx, xy, yx, yy]: [i32; 4],
+    f: &mut impl FnMut(i32, i32),
+) {
+    if start < end {
+        return;
+    }
+
+    let mut new_start = 0.0;
+    for j in row..=radius {
+        let dy = -j;
+        let mut blocked = false;
+
+        for dx in -j..=0 {
+            let (x, y) = (cx + dx * xx + dy * xy, cy + dx * yx + dy * yy);
+            let l_slope = (dx as f32 - 0.5) / (dy as f32 + 0.5);
+            let r_slope = (dx as f32 + 0.5) / (dy as f32 - 0.5);
+
+            if start < r_slope {
+                continue;
+            } else if end > l_slope {
+                break;
+            }
+
+            if dx * dx + dy * dy <= radius * radius {
+                f(x, y);
+            }
+
+            if blocked {
+                if opaque(map, x, y) {
+                    new_start = r_slope;
+                } else {
+                    blocked = false;
+                    start = new_start;
+                }
+            } else if opaque(map, x, y) && j < radius {
+                blocked = true;
+                cast(
+                    map,
+                    (cx, cy),
+                    j + 1,
+                    start,
+                    l_slope,
+                    radius,
+                    [xx, xy, yx, yy],
+                    f,
+                );
+                new_start = r_slope;
+            }
+        }
+
+        if blocked {
+            break;
+        }
+    }
+}
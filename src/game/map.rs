@@ -6,122 +6,261 @@ pub struct Boss {
     pub pos: Option<Position>,
 }
 
+// the classic 400x400 map has about 144k walkable tiles (measured over seeds with the
+// mapgen tool) and always started with 1024 zombies and 480 chests, every other map
+// gets that same density over its own walkable area
+const CLASSIC_AREA: i64 = 144_000;
+const CLASSIC_ENEMIES: i64 = 1024;
+const CLASSIC_CHESTS: i64 = 480;
+
+// zombies at the start of a generated map with `area` walkable tiles
+pub fn enemies_for(area: i32) -> i32 {
+    (area as i64 * CLASSIC_ENEMIES / CLASSIC_AREA) as i32
+}
+pub fn chests_for(area: i32) -> i32 {
+    (area as i64 * CLASSIC_CHESTS / CLASSIC_AREA) as i32
+}
+
+// spots a map marks for spawning, empty lists mean anywhere open
+#[derive(Clone, Default)]
+pub struct SpawnPoints {
+    pub chests: Vec<(i32, i32)>,
+    pub zones: Vec<(i32, i32)>,
+    pub bosses: Vec<(i32, i32)>,
+}
+
+// the terrain a map starts with, generated or read from a map file
+pub struct MapLayout {
+    pub w: usize,
+    pub h: usize,
+    pub tiles: Vec<Tile>,
+    pub start: (i32, i32),
+    pub points: SpawnPoints,
+}
+
+impl MapLayout {
+    // the same `rng` seed always gives the same terrain
+    pub fn generate(
+        generator: &dyn MapGenerator,
+        params: &MapParams,
+        rng: &mut dyn RngCore,
+    ) -> Self {
+        let start = (params.w as i32 / 2, params.h as i32 / 2);
+        let mut tiles = generator.generate(params, rng);
+        params.shape.apply(&mut tiles, params.w, params.h, rng);
+        // islands end up joined by tunnels through the rock between them
+        connect(&mut tiles, params.w, params.h, start);
+
+        MapLayout {
+            w: params.w,
+            h: params.h,
+            tiles,
+            start,
+            points: SpawnPoints::default(),
+        }
+    }
+}
+
 pub struct Map {
     pub bullets: Vec<Bullet>,
-    pub pass: MultiVec<bool>,
-    pub enemies: MultiVec<Option<Enemy>>,
-    pub tiles: MultiVec<Tile>,
+    pub pass: Chunks<bool>,
+    // walkable from the start, nothing spawns outside of it
+    pub reach: Chunks<bool>,
+    pub enemies: Chunks<Option<Enemy>>,
+    pub tiles: Chunks<Tile>,
     pub bombs: Vec<Bomb>,
     pub turrets: Vec<Turret>,
     pub emps: Vec<Emp>,
     pub chests: VecDeque<(i32, i32)>,
     pub boss: Boss,
+    // drained by the screen every tick
+    pub events: Vec<GameEvent>,
     pub spawned: i32,
+    pub start: (i32, i32),
+    pub points: SpawnPoints,
+    // streams chunks in and out in the endless mode
+    pub world: Option<World>,
     pub w: i32,
     pub h: i32,
 }
 
 impl Map {
-    pub fn new() -> Map {
-        let (w, h) = (400, 400);
-
-        let bullets = vec![];
-
-        let tiles = Map::generate(w, h);
-
-        let mut pass = MultiVec {
-            inner: tiles.iter().map(|t| matches!(t, Tile::None)).collect(),
+    // an empty map, unloaded ground reads as solid wall
+    fn blank(w: i32, h: i32, start: (i32, i32), points: SpawnPoints) -> Map {
+        Map {
+            bullets: vec![],
+            pass: Chunks::new(w, h, false),
+            reach: Chunks::new(w, h, false),
+            enemies: Chunks::new(w, h, None),
+            tiles: Chunks::new(w, h, Tile::Wall(Wall::whole())),
+            bombs: vec![],
+            turrets: vec![],
+            emps: vec![],
+            chests: VecDeque::new(),
+            boss: Boss {
+                nth: 0,
+                resp: None,
+                pos: None,
+            },
+            events: vec![],
+            spawned: 0,
+            start,
+            points,
+            world: None,
             w,
+            h,
+        }
+    }
+    pub fn new(layout: MapLayout) -> Map {
+        let MapLayout {
+            w,
+            h,
+            tiles,
+            start,
+            points,
+        } = layout;
+        let (w, h) = (w as i32, h as i32);
+
+        let mut map = Map::blank(w, h, start, points);
+        let pass = tiles.iter().map(Tile::walkable).collect();
+        map.pass = Chunks::from_vec(w, h, pass, false);
+        map.reach = Chunks::from_vec(w, h, vec![false; (w * h) as usize], false);
+        map.enemies = Chunks::from_vec(w, h, vec![None; (w * h) as usize], None);
+        map.tiles = Chunks::from_vec(w, h, tiles, Tile::Wall(Wall::whole()));
+        spread(&mut map.reach, &map.tiles, start);
+
+        // generated maps fill up by walkable area, hand made ones a share of their marked spots
+        let area = map.reach.values().filter(|&&r| r).count() as i32;
+        let enemies = if map.points.zones.is_empty() {
+            enemies_for(area)
+        } else {
+            map.points.zones.len() as i32 / 16
         };
-
-        let mut tiles = MultiVec { inner: tiles, w };
-
-        let spawn = 1024;
-        let enemies = Map::spawn_enemies(spawn, &mut pass, w, h);
-
-        let bombs = vec![];
-        let turrets = vec![];
-        let emps = vec![];
-
-        let chests = Map::spawn_chests(480, &mut pass, &mut tiles, w as i32, h as i32);
-
-        let boss = Boss {
-            nth: 0,
-            resp: None,
-            pos: None,
+        let chests = if map.points.chests.is_empty() {
+            chests_for(area)
+        } else {
+            map.points.chests.len() as i32 / 2
         };
 
-        Map {
-            bullets,
-            pass,
-            enemies,
-            tiles,
-            bombs,
-            turrets,
-            emps,
-            chests,
-            boss,
-            spawned: spawn,
-            w: w as i32,
-            h: h as i32,
-        }
+        let all = [0, 0, w, h];
+        map.spawn_enemies(enemies, all, start);
+        map.spawn_chests(chests, all);
+        map
     }
-    fn spawn_chests(
-        n: i32,
-        pass: &mut MultiVec<bool>,
-        tiles: &mut MultiVec<Tile>,
-        w: i32,
-        h: i32,
-    ) -> VecDeque<(i32, i32)> {
-        let mut chests = VecDeque::with_capacity(n as usize);
-        let mut rng = thread_rng();
-
-        let mut i = 0;
-        while i < n {
-            let x = rng.gen_range(0..w);
-            let y = rng.gen_range(0..h);
-
-            if !pass[(x, y)] {
-                continue;
-            }
-
-            let ty = ChestType::random();
-            tiles[(x, y)] = Tile::Chest(Chest {
-                start: 0,
-                duration: 60 * 60 * 4,
-                ty,
-            });
-            chests.push_back((x, y));
+    // the endless mode, only the chunks around the start exist at first
+    pub fn endless(generator: Box<dyn MapGenerator>) -> Map {
+        // the middle of a chunk, where every chunk's ground is tunneled to
+        let start = (ENDLESS / 2 + CHUNK / 2, ENDLESS / 2 + CHUNK / 2);
+        let mut map = Map::blank(ENDLESS, ENDLESS, start, SpawnPoints::default());
+        map.world = Some(World::new(generator));
+        map.stream(start);
+        map
+    }
+    // brings in the chunks around the player and puts away the ones left behind
+    pub fn stream(&mut self, (px, py): (i32, i32)) {
+        let Some(mut world) = self.world.take() else {
+            return;
+        };
+        world.stream(self, (px, py));
+        self.world = Some(world);
+    }
+    // takes `dmg` off the wall, door or barricade at `(x, y)` and turns it into floor once
+    // it's out of hp, true if it broke. the outer ring holds so nothing gets off the map
+    pub fn chip(&mut self, x: i32, y: i32, dmg: i8) -> bool {
+        if x <= 0
+            || y <= 0
+            || x >= self.w - 1
+            || y >= self.h - 1
+            || !self.tiles.loaded(Chunks::<Tile>::chunk_of(x, y))
+        {
+            return false;
+        }
 
-            i += 1;
+        let hp = match self.tiles[(x, y)] {
+            Tile::Wall(Wall { ref mut hp })
+            | Tile::MovableWall(MovableWall { ref mut hp })
+            | Tile::Door(Door { ref mut hp, .. })
+            | Tile::Barricade(Barricade { ref mut hp }) => hp,
+            _ => return false,
+        };
+        *hp = hp.saturating_sub(dmg);
+        if *hp > 0 {
+            return false;
         }
 
-        chests
+        self.tiles[(x, y)] = Tile::None;
+        // whoever stood in an open doorway is still there
+        self.pass[(x, y)] = self.enemies[(x, y)].is_none();
+        join(&mut self.reach, &self.tiles, (x, y));
+        true
     }
-    fn spawn_enemies(
-        n: i32,
-        pass: &mut MultiVec<bool>,
-        w: usize,
-        h: usize,
-    ) -> MultiVec<Option<Enemy>> {
-        let mut enemies = MultiVec {
-            inner: vec![None; w * h],
-            w,
+    pub fn reachable(&self, x: i32, y: i32) -> bool {
+        self.reach[(x, y)]
+    }
+    // where things may spawn, the live chunks in the endless mode
+    pub fn bounds(&self) -> [i32; 4] {
+        match self.world {
+            Some(ref world) => world.bounds(),
+            None => [0, 0, self.w, self.h],
+        }
+    }
+    // a random open tile at least `min` away from `from`, taken from `points` unless that's empty
+    pub fn spawn_point(
+        &self,
+        points: &[(i32, i32)],
+        from: (i32, i32),
+        min: i32,
+    ) -> Option<(i32, i32)> {
+        self.pick(points, from, min, self.bounds())
+    }
+    // like `spawn_point` but only inside `[x0, y0, x1, y1)`
+    fn pick(
+        &self,
+        points: &[(i32, i32)],
+        from: (i32, i32),
+        min: i32,
+        [x0, y0, x1, y1]: [i32; 4],
+    ) -> Option<(i32, i32)> {
+        let free = |(x, y): (i32, i32)| {
+            self.reachable(x, y)
+                && self.pass[(x, y)]
+                && matches!(self.tiles[(x, y)], Tile::None)
+                && dist(x, y, from.0, from.1) >= min
         };
 
         let mut rng = thread_rng();
-        let mut i = 0;
-        while i < n {
-            let x = rng.gen_range(0..w) as i32;
-            let y = rng.gen_range(0..h) as i32;
-
-            if !pass[(x, y)] || dist(x, y, w as i32 / 2, h as i32 / 2) <= 20 {
-                continue;
+        if !points.is_empty() {
+            let open: Vec<_> = points.iter().copied().filter(|&p| free(p)).collect();
+            if open.is_empty() {
+                return None;
             }
+            return Some(open[rng.gen_range(0..open.len())]);
+        }
 
-            pass[(x, y)] = false;
-            enemies[(x, y)] = Some(Enemy {
-                uid: i,
+        // gives up eventually so a crowded map can't hang the tick
+        (0..10_000)
+            .map(|_| (rng.gen_range(x0..x1), rng.gen_range(y0..y1)))
+            .find(|&p| free(p))
+    }
+    pub fn spawn_chests(&mut self, n: i32, area: [i32; 4]) {
+        for _ in 0..n {
+            let Some((x, y)) = self.pick(&self.points.chests, self.start, 0, area) else {
+                break;
+            };
+            spawn_chest(self, 0, x, y, ChestType::random());
+        }
+    }
+    // zombies keep their distance from `from`
+    pub fn spawn_enemies(&mut self, n: i32, area: [i32; 4], from: (i32, i32)) {
+        let mut rng = thread_rng();
+        for _ in 0..n {
+            let Some((x, y)) = self.pick(&self.points.zones, from, 21, area) else {
+                break;
+            };
+
+            self.pass[(x, y)] = false;
+            self.enemies[(x, y)] = Some(Enemy {
+                uid: self.spawned,
                 hp: 1,
                 mhp: 1,
                 last: rng.gen_range(0..=120),
@@ -130,79 +269,8 @@ impl Map {
                 immobilized: false,
                 ty: EnemyType::Zombie,
             });
-
-            i += 1;
-        }
-
-        enemies
-    }
-    fn generate(w: usize, h: usize) -> Vec<Tile> {
-        // generate random structures on map
-        let mut tiles = vec![Tile::None; w * h];
-
-        // map borders
-        for i in 0..w {
-            tiles[i] = Tile::Wall(Wall { bullets: 0 });
-            tiles[i + w * (h - 1)] = Tile::Wall(Wall { bullets: 0 });
-        }
-        for i in 0..h {
-            tiles[i * w] = Tile::Wall(Wall { bullets: 0 });
-            tiles[i * w + (w - 1)] = Tile::Wall(Wall { bullets: 0 });
+            self.spawned += 1;
         }
-
-        let mut rng = rand::thread_rng();
-        for x in 1..w - 1 {
-            for y in 1..h - 1 {
-                if dist(w as i32 / 2, h as i32 / 2, x as i32, y as i32) < 10 {
-                    continue;
-                }
-                // ##
-                // #X <- X is current position, we dont want wall here
-                match (
-                    &tiles[x - 1 + (y - 1) * w],
-                    &tiles[x - 1 + y * w],
-                    &tiles[x + (y - 1) * w],
-                ) {
-                    (Tile::Wall(_), Tile::Wall(_), Tile::Wall(_)) => continue,
-                    _ => {}
-                };
-
-                let mut walls = 0;
-                for z in -1i32..=1 {
-                    for t in -1i32..=1 {
-                        if matches!(
-                            tiles[(x as i32 + z) as usize + (y as i32 + t) as usize * w],
-                            Tile::Wall(_)
-                        ) {
-                            walls += 1;
-                        }
-                    }
-                }
-
-                if walls > 0 && rng.gen_range(0..100) < 35 {
-                    tiles[x + y * w] = if rng.gen_range(0..100) < 70 {
-                        Tile::Wall(Wall { bullets: 0 })
-                    } else {
-                        Tile::MovableWall(MovableWall { bullets: 0 })
-                    };
-                } else if rng.gen_range(0..100) < 3 {
-                    tiles[x + y * w] = Tile::Wall(Wall { bullets: 0 });
-                }
-            }
-        }
-        for x in 1..w - 1 {
-            for y in 1..h - 1 {
-                if matches!(tiles[x - 1 + y * w], Tile::Wall(_) | Tile::MovableWall(_))
-                    && matches!(tiles[x + 1 + y * w], Tile::Wall(_) | Tile::MovableWall(_))
-                    && matches!(tiles[x + (y - 1) * w], Tile::Wall(_) | Tile::MovableWall(_))
-                    && matches!(tiles[x + (y + 1) * w], Tile::Wall(_) | Tile::MovableWall(_))
-                {
-                    tiles[x + y * w] = Tile::MovableWall(MovableWall { bullets: 0 });
-                }
-            }
-        }
-
-        tiles
     }
 }
 
@@ -212,45 +280,96 @@ pub enum Tile {
     Wall(Wall),
     MovableWall(MovableWall),
     Chest(Chest),
+    // the player opens and closes them, zombies have to break closed ones down
+    Door(Door),
+    // slows down whoever wades through
+    Water,
+    // hurts zombies stepping on them
+    Spikes,
+    // built by the player, stops zombies until it's broken
+    Barricade(Barricade),
 }
 
-#[derive(Clone)]
-pub struct Wall {
-    pub bullets: i8,
+impl Tile {
+    // blocks light and sight
+    pub fn opaque(&self) -> bool {
+        matches!(
+            self,
+            Tile::Wall(_) | Tile::MovableWall(_) | Tile::Door(Door { open: false, .. })
+        )
+    }
+    // ground anyone can stand on when nothing else is there
+    pub fn walkable(&self) -> bool {
+        matches!(
+            self,
+            Tile::None | Tile::Water | Tile::Spikes | Tile::Door(Door { open: true, .. })
+        )
+    }
+    // in the way of zombies until they break it
+    pub fn breakable(&self) -> bool {
+        matches!(
+            self,
+            Tile::Door(Door { open: false, .. }) | Tile::Barricade(_)
+        )
+    }
+    // how far a wall, door or barricade has been chipped down, 0 while whole
+    pub fn wear(&self) -> f32 {
+        let (hp, max) = match self {
+            Tile::Wall(wall) => (wall.hp, WALL_HP),
+            Tile::MovableWall(wall) => (wall.hp, MOVABLE_WALL_HP),
+            Tile::Door(door) => (door.hp, DOOR_HP),
+            Tile::Barricade(barricade) => (barricade.hp, BARRICADE_HP),
+            _ => return 0.0,
+        };
+        1.0 - hp as f32 / max as f32
+    }
 }
 
+pub const WALL_HP: i8 = 12;
+pub const MOVABLE_WALL_HP: i8 = 6;
+pub const DOOR_HP: i8 = 6;
+pub const BARRICADE_HP: i8 = 4;
+
 #[derive(Clone)]
-pub struct MovableWall {
-    pub bullets: i8,
+pub struct Door {
+    pub open: bool,
+    pub hp: i8,
 }
 
-pub struct MultiVec<T> {
-    pub inner: Vec<T>,
-    pub w: usize,
+impl Door {
+    pub fn closed() -> Self {
+        Door {
+            open: false,
+            hp: DOOR_HP,
+        }
+    }
 }
 
-impl<T> Index<(i32, i32)> for MultiVec<T> {
-    type Output = T;
-    fn index(&self, (x, y): (i32, i32)) -> &Self::Output {
-        &self.inner[y as usize * self.w..][..self.w][x as usize]
-    }
+#[derive(Clone)]
+pub struct Barricade {
+    pub hp: i8,
 }
 
-impl<T> IndexMut<(i32, i32)> for MultiVec<T> {
-    fn index_mut(&mut self, (x, y): (i32, i32)) -> &mut Self::Output {
-        &mut self.inner[y as usize * self.w..][..self.w][x as usize]
-    }
+#[derive(Clone)]
+pub struct Wall {
+    pub hp: i8,
 }
 
-impl<T> Index<usize> for MultiVec<T> {
-    type Output = T;
-    fn index(&self, idx: usize) -> &Self::Output {
-        &self.inner[idx]
+impl Wall {
+    pub fn whole() -> Self {
+        Wall { hp: WALL_HP }
     }
 }
 
-impl<T> IndexMut<usize> for MultiVec<T> {
-    fn index_mut(&mut self, idx: usize) -> &mut Self::Output {
-        &mut self.inner[idx]
+#[derive(Clone)]
+pub struct MovableWall {
+    pub hp: i8,
+}
+
+impl MovableWall {
+    pub fn whole() -> Self {
+        MovableWall {
+            hp: MOVABLE_WALL_HP,
+        }
     }
 }
@@ -0,0 +1,307 @@
+use super::*;
+
+// hand made arenas are plain text, one character per tile
+//
+//   #  wall              +  movable wall
+//   .  floor             c  chest spawn
+//   @  player start      z  zombie spawn zone
+//   B  boss spawn        D  door
+//   ~  water             ^  spikes
+//   =  barricade
+//
+// lines starting with `;` are comments, every row has to be the same width
+// and the outer ring has to be walls
+
+#[derive(Debug)]
+pub struct MapError {
+    pub line: usize,
+    pub col: usize,
+    pub msg: String,
+}
+
+impl std::fmt::Display for MapError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "line {}, column {}: {}", self.line, self.col, self.msg)
+    }
+}
+
+impl std::error::Error for MapError {}
+
+fn error(line: usize, col: usize, msg: impl Into<String>) -> MapError {
+    MapError {
+        line,
+        col,
+        msg: msg.into(),
+    }
+}
+
+pub fn parse_map(src: &str) -> std::result::Result<MapLayout, MapError> {
+    // (line number, row) pairs, line numbers count comments too
+    let rows: Vec<(usize, Vec<char>)> = src
+        .lines()
+        .enumerate()
+        .filter(|(_, l)| !l.starts_with(';') && !l.trim().is_empty())
+        .map(|(n, l)| (n + 1, l.trim_end().chars().collect()))
+        .collect();
+
+    let Some((first, top)) = rows.first() else {
+        return Err(error(1, 1, "map is empty"));
+    };
+    let (w, h) = (top.len(), rows.len());
+    if w < 3 || h < 3 {
+        return Err(error(*first, 1, "map has to be at least 3x3"));
+    }
+
+    let mut tiles = Vec::with_capacity(w * h);
+    let mut start = None;
+    let mut points = SpawnPoints::default();
+
+    for (y, (line, row)) in rows.iter().enumerate() {
+        if row.len() != w {
+            return Err(error(
+                *line,
+                row.len().min(w) + 1,
+                format!("row is {} wide, expected {}", row.len(), w),
+            ));
+        }
+
+        for (x, &c) in row.iter().enumerate() {
+            let (line, col) = (*line, x + 1);
+            let pos = (x as i32, y as i32);
+
+            let border = x == 0 || y == 0 || x == w - 1 || y == h - 1;
+            if border && c != '#' {
+                return Err(error(
+                    line,
+                    col,
+                    format!("'{}' on the border, expected '#'", c),
+                ));
+            }
+
+            tiles.push(match c {
+                '#' => Tile::Wall(Wall::whole()),
+                '+' => Tile::MovableWall(MovableWall::whole()),
+                '.' => Tile::None,
+                'D' => Tile::Door(Door::closed()),
+                '~' => Tile::Water,
+                '^' => Tile::Spikes,
+                '=' => Tile::Barricade(Barricade { hp: BARRICADE_HP }),
+                'c' => {
+                    points.chests.push(pos);
+                    Tile::None
+                }
+                'z' => {
+                    points.zones.push(pos);
+                    Tile::None
+                }
+                'B' => {
+                    points.bosses.push(pos);
+                    Tile::None
+                }
+                '@' => {
+                    if start.is_some() {
+                        return Err(error(line, col, "second player start"));
+                    }
+                    start = Some(pos);
+                    Tile::None
+                }
+                _ => return Err(error(line, col, format!("unknown tile '{}'", c))),
+            });
+        }
+    }
+
+    let Some(start) = start else {
+        return Err(error(*first, 1, "no player start '@'"));
+    };
+
+    Ok(MapLayout {
+        w,
+        h,
+        tiles,
+        start,
+        points,
+    })
+}
+
+// maps shipped with the game
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Arena {
+    Pit,
+    Crossroads,
+    Fortress,
+}
+
+impl Arena {
+    pub const ALL: [Arena; 3] = [Arena::Pit, Arena::Crossroads, Arena::Fortress];
+    fn source(self) -> (&'static str, &'static str) {
+        match self {
+            Arena::Pit => ("pit.map", include_str!("../../assets/maps/pit.map")),
+            Arena::Crossroads => (
+                "crossroads.map",
+                include_str!("../../assets/maps/crossroads.map"),
+            ),
+            Arena::Fortress => (
+                "fortress.map",
+                include_str!("../../assets/maps/fortress.map"),
+            ),
+        }
+    }
+    pub fn layout(self) -> MapLayout {
+        let (file, src) = self.source();
+        parse_map(src).unwrap_or_else(|e| panic!("Invalid map {}: {}", file, e))
+    }
+    pub fn id(self) -> &'static str {
+        match self {
+            Arena::Pit => "map.pit",
+            Arena::Crossroads => "map.crossroads",
+            Arena::Fortress => "map.fortress",
+        }
+    }
+}
+
+// what the menu picks for the next run
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MapSource {
+    Generated(Generator),
+    Bundled(Arena),
+    // chunks of a generator streamed in forever
+    Endless(Generator),
+}
+
+impl MapSource {
+    pub fn all() -> Vec<MapSource> {
+        Generator::ALL
+            .into_iter()
+            .map(MapSource::Generated)
+            .chain(Arena::ALL.into_iter().map(MapSource::Bundled))
+            .chain(Generator::ALL.into_iter().map(MapSource::Endless))
+            .collect()
+    }
+    // size and shape only change generated maps, arenas are drawn at their own
+    pub fn build(self, size: MapSize, shape: Shape) -> Map {
+        match self {
+            MapSource::Generated(g) => Map::new(MapLayout::generate(
+                g.build().as_ref(),
+                &MapParams::sized(size, shape),
+                &mut thread_rng(),
+            )),
+            MapSource::Bundled(arena) => Map::new(arena.layout()),
+            MapSource::Endless(g) => Map::endless(g.build()),
+        }
+    }
+    pub fn id(self) -> &'static str {
+        match self {
+            MapSource::Generated(g) => g.id(),
+            MapSource::Bundled(arena) => arena.id(),
+            MapSource::Endless(_) => "map.endless",
+        }
+    }
+    // the endless mode goes by the generator it streams too
+    pub fn name(self, lang: &Lang) -> String {
+        match self {
+            MapSource::Endless(g) => format!("{}: {}", lang.get(self.id()), lang.get(g.id())),
+            _ => String::from(lang.get(self.id())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error_at(src: &str) -> (usize, usize, String) {
+        match parse_map(src) {
+            Ok(_) => panic!("{:?} parsed", src),
+            Err(e) => (e.line, e.col, e.msg),
+        }
+    }
+
+    #[test]
+    fn every_tile_of_the_legend() {
+        let layout = parse_map(
+            "; a comment, not a row\n\
+             #####\n\
+             #@+c#\n\
+             #D~^#\n\
+             #=zB#\n\
+             #####\n",
+        )
+        .unwrap();
+
+        assert_eq!((layout.w, layout.h), (5, 5));
+        assert_eq!(layout.start, (1, 1));
+        assert_eq!(layout.points.chests, [(3, 1)]);
+        assert_eq!(layout.points.zones, [(2, 3)]);
+        assert_eq!(layout.points.bosses, [(3, 3)]);
+
+        let tile = |x: usize, y: usize| &layout.tiles[x + y * layout.w];
+        assert!(matches!(tile(0, 0), Tile::Wall(_)));
+        assert!(matches!(tile(1, 1), Tile::None));
+        assert!(matches!(tile(2, 1), Tile::MovableWall(_)));
+        assert!(matches!(tile(3, 1), Tile::None));
+        assert!(matches!(tile(1, 2), Tile::Door(Door { open: false, .. })));
+        assert!(matches!(tile(2, 2), Tile::Water));
+        assert!(matches!(tile(3, 2), Tile::Spikes));
+        assert!(matches!(
+            tile(1, 3),
+            Tile::Barricade(Barricade { hp: BARRICADE_HP })
+        ));
+        assert!(matches!(tile(2, 3), Tile::None));
+        assert!(matches!(tile(3, 3), Tile::None));
+    }
+
+    #[test]
+    fn errors_point_at_the_line_and_column() {
+        assert_eq!(error_at(""), (1, 1, "map is empty".into()));
+        assert_eq!(
+            error_at("; only\n; comments\n"),
+            (1, 1, "map is empty".into())
+        );
+        assert_eq!(
+            error_at("##\n##\n"),
+            (1, 1, "map has to be at least 3x3".into())
+        );
+        assert_eq!(
+            error_at("###\n#@##\n###\n"),
+            (2, 4, "row is 4 wide, expected 3".into())
+        );
+        assert_eq!(
+            error_at("####\n#@#\n####\n"),
+            (2, 4, "row is 3 wide, expected 4".into())
+        );
+        assert_eq!(
+            error_at("###\n.@#\n###\n"),
+            (2, 1, "'.' on the border, expected '#'".into())
+        );
+        assert_eq!(
+            error_at("####\n#@x#\n####\n"),
+            (2, 3, "unknown tile 'x'".into())
+        );
+        assert_eq!(
+            error_at("####\n#@@#\n####\n"),
+            (2, 3, "second player start".into())
+        );
+        assert_eq!(
+            error_at("###\n#.#\n###\n"),
+            (1, 1, "no player start '@'".into())
+        );
+    }
+
+    #[test]
+    fn line_numbers_count_comments() {
+        assert_eq!(
+            error_at("; the first line\n\n####\n#@x#\n####\n"),
+            (4, 3, "unknown tile 'x'".into())
+        );
+    }
+
+    #[test]
+    fn shipped_arenas_parse() {
+        for arena in Arena::ALL {
+            let (file, src) = arena.source();
+            if let Err(e) = parse_map(src) {
+                panic!("{}: {}", file, e);
+            }
+        }
+    }
+}
@@ -0,0 +1,495 @@
+use super::*;
+
+// the area around the player start every generator leaves open
+const START_CLEAR: i32 = 10;
+
+pub struct MapParams {
+    pub w: usize,
+    pub h: usize,
+    // scales how much of the map gets covered, 1.0 is what each generator was tuned for
+    pub density: f32,
+    pub shape: Shape,
+    // leave the middle open for the player, off for endless chunks
+    pub clear_start: bool,
+}
+
+impl MapParams {
+    pub fn new(w: usize, h: usize) -> Self {
+        MapParams {
+            w,
+            h,
+            density: 1.0,
+            shape: Shape::Square,
+            clear_start: true,
+        }
+    }
+    pub fn sized(size: MapSize, shape: Shape) -> Self {
+        let side = size.side();
+        let (w, h) = match shape {
+            Shape::Wide => (side * 3 / 2, side * 3 / 4),
+            _ => (side, side),
+        };
+        MapParams {
+            shape,
+            ..MapParams::new(w, h)
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MapSize {
+    Small,
+    Medium,
+    Large,
+    Huge,
+}
+
+impl MapSize {
+    pub const ALL: [MapSize; 4] = [
+        MapSize::Small,
+        MapSize::Medium,
+        MapSize::Large,
+        MapSize::Huge,
+    ];
+    pub fn side(self) -> usize {
+        match self {
+            MapSize::Small => 200,
+            MapSize::Medium => 300,
+            MapSize::Large => 400,
+            MapSize::Huge => 600,
+        }
+    }
+    pub fn id(self) -> &'static str {
+        match self {
+            MapSize::Small => "size.small",
+            MapSize::Medium => "size.medium",
+            MapSize::Large => "size.large",
+            MapSize::Huge => "size.huge",
+        }
+    }
+}
+
+// the outline of a generated map, everything outside is solid wall
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Shape {
+    Square,
+    Wide,
+    Circle,
+    Islands,
+}
+
+impl Shape {
+    pub const ALL: [Shape; 4] = [Shape::Square, Shape::Wide, Shape::Circle, Shape::Islands];
+    pub fn id(self) -> &'static str {
+        match self {
+            Shape::Square => "shape.square",
+            Shape::Wide => "shape.wide",
+            Shape::Circle => "shape.circle",
+            Shape::Islands => "shape.islands",
+        }
+    }
+    // walls over whatever falls outside, the start in the center is always inside
+    pub fn apply(self, tiles: &mut [Tile], w: usize, h: usize, rng: &mut dyn RngCore) {
+        let (cx, cy) = (w as f32 / 2.0, h as f32 / 2.0);
+        // (x, y, radius) of each island, the first one holds the start
+        let blobs: Vec<(f32, f32, f32)> = match self {
+            Shape::Square | Shape::Wide => return,
+            Shape::Circle => vec![(cx, cy, cx.min(cy) - 1.0)],
+            Shape::Islands => {
+                let r = cx.min(cy);
+                let mut blobs = vec![(cx, cy, r * 0.25)];
+                for _ in 0..rng.gen_range(5..=8) {
+                    let size = rng.gen_range(0.1..0.22) * r;
+                    blobs.push((
+                        rng.gen_range(size + 1.0..w as f32 - size - 1.0),
+                        rng.gen_range(size + 1.0..h as f32 - size - 1.0),
+                        size,
+                    ));
+                }
+                blobs
+            }
+        };
+
+        for y in 0..h {
+            for x in 0..w {
+                let inside = blobs.iter().any(|&(bx, by, r)| {
+                    (x as f32 + 0.5 - bx).powi(2) + (y as f32 + 0.5 - by).powi(2) < r * r
+                });
+                if !inside {
+                    tiles[x + y * w] = wall();
+                }
+            }
+        }
+    }
+}
+
+// fills a `w * h` grid, the border is walled off and the center is kept clear afterwards
+pub trait MapGenerator {
+    fn generate(&self, params: &MapParams, rng: &mut dyn RngCore) -> Vec<Tile>;
+}
+
+// picked on the menu and stored so the next run uses it again
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Generator {
+    Classic,
+    Rooms,
+    Caves,
+    City,
+    Field,
+}
+
+impl Generator {
+    pub const ALL: [Generator; 5] = [
+        Generator::Classic,
+        Generator::Rooms,
+        Generator::Caves,
+        Generator::City,
+        Generator::Field,
+    ];
+    pub fn build(self) -> Box<dyn MapGenerator> {
+        match self {
+            Generator::Classic => Box::new(Classic),
+            Generator::Rooms => Box::new(Rooms),
+            Generator::Caves => Box::new(Caves),
+            Generator::City => Box::new(City),
+            Generator::Field => Box::new(Field),
+        }
+    }
+    pub fn id(self) -> &'static str {
+        match self {
+            Generator::Classic => "map.classic",
+            Generator::Rooms => "map.rooms",
+            Generator::Caves => "map.caves",
+            Generator::City => "map.city",
+            Generator::Field => "map.field",
+        }
+    }
+}
+
+fn wall() -> Tile {
+    Tile::Wall(Wall::whole())
+}
+
+fn movable() -> Tile {
+    Tile::MovableWall(MovableWall::whole())
+}
+
+fn solid(tile: &Tile) -> bool {
+    matches!(tile, Tile::Wall(_) | Tile::MovableWall(_))
+}
+
+// border walls and an open start, shared by every generator
+fn finish(tiles: &mut [Tile], params: &MapParams) {
+    let (w, h) = (params.w, params.h);
+    let (cx, cy) = (w as i32 / 2, h as i32 / 2);
+    for x in 0..w {
+        for y in 0..h {
+            if x == 0 || y == 0 || x == w - 1 || y == h - 1 {
+                tiles[x + y * w] = wall();
+            } else if params.clear_start && dist(cx, cy, x as i32, y as i32) < START_CLEAR {
+                tiles[x + y * w] = Tile::None;
+            }
+        }
+    }
+}
+
+fn chance(rng: &mut dyn RngCore, p: f32) -> bool {
+    rng.gen::<f32>() < p
+}
+
+// walls grow out of a few random seeds, the original generator
+pub struct Classic;
+
+impl MapGenerator for Classic {
+    fn generate(&self, params: &MapParams, rng: &mut dyn RngCore) -> Vec<Tile> {
+        let (w, h) = (params.w, params.h);
+        let mut tiles = vec![Tile::None; w * h];
+        finish(&mut tiles, params);
+
+        let grow = 0.35 * params.density;
+        let seed = 0.03 * params.density;
+        for x in 1..w - 1 {
+            for y in 1..h - 1 {
+                if params.clear_start
+                    && dist(w as i32 / 2, h as i32 / 2, x as i32, y as i32) < START_CLEAR
+                {
+                    continue;
+                }
+                // ##
+                // #X <- X is current position, we dont want wall here
+                match (
+                    &tiles[x - 1 + (y - 1) * w],
+                    &tiles[x - 1 + y * w],
+                    &tiles[x + (y - 1) * w],
+                ) {
+                    (Tile::Wall(_), Tile::Wall(_), Tile::Wall(_)) => continue,
+                    _ => {}
+                };
+
+                let mut walls = 0;
+                for z in -1i32..=1 {
+                    for t in -1i32..=1 {
+                        if matches!(
+                            tiles[(x as i32 + z) as usize + (y as i32 + t) as usize * w],
+                            Tile::Wall(_)
+                        ) {
+                            walls += 1;
+                        }
+                    }
+                }
+
+                if walls > 0 && chance(rng, grow) {
+                    tiles[x + y * w] = if chance(rng, 0.7) { wall() } else { movable() };
+                } else if chance(rng, seed) {
+                    tiles[x + y * w] = wall();
+                }
+            }
+        }
+        // single gaps boxed in on all sides would be unreachable
+        for x in 1..w - 1 {
+            for y in 1..h - 1 {
+                if solid(&tiles[x - 1 + y * w])
+                    && solid(&tiles[x + 1 + y * w])
+                    && solid(&tiles[x + (y - 1) * w])
+                    && solid(&tiles[x + (y + 1) * w])
+                {
+                    tiles[x + y * w] = movable();
+                }
+            }
+        }
+
+        tiles
+    }
+}
+
+// rectangular rooms carved out of solid rock, each joined to the previous by a corridor
+pub struct Rooms;
+
+impl Rooms {
+    fn carve(tiles: &mut [Tile], w: usize, (x0, y0): (usize, usize), (x1, y1): (usize, usize)) {
+        for y in y0.min(y1)..=y0.max(y1) {
+            for x in x0.min(x1)..=x0.max(x1) {
+                tiles[x + y * w] = Tile::None;
+            }
+        }
+    }
+}
+
+impl MapGenerator for Rooms {
+    fn generate(&self, params: &MapParams, rng: &mut dyn RngCore) -> Vec<Tile> {
+        let (w, h) = (params.w, params.h);
+        let mut tiles = vec![wall(); w * h];
+
+        // denser maps get fewer rooms and narrower corridors
+        let count = ((w * h) as f32 / 1200.0 / params.density.max(0.1)) as usize;
+        let corridor = if params.density > 1.2 { 1 } else { 2 };
+
+        let mut centers = vec![(w / 2, h / 2)];
+        for _ in 0..count {
+            let rw = rng.gen_range(6..20).min(w - 4);
+            let rh = rng.gen_range(6..20).min(h - 4);
+            let x = rng.gen_range(2..w - rw - 1);
+            let y = rng.gen_range(2..h - rh - 1);
+            Self::carve(&mut tiles, w, (x, y), (x + rw - 1, y + rh - 1));
+            centers.push((x + rw / 2, y + rh / 2));
+        }
+
+        for k in 1..centers.len() {
+            let (ax, ay) = centers[k - 1];
+            let (bx, by) = centers[k];
+            // L shaped, horizontal leg first half of the time
+            let corner = if rng.gen_bool(0.5) {
+                (bx, ay)
+            } else {
+                (ax, by)
+            };
+            for (from, to) in [((ax, ay), corner), (corner, (bx, by))] {
+                Self::carve(
+                    &mut tiles,
+                    w,
+                    (from.0.max(1), from.1.max(1)),
+                    (
+                        (to.0 + corridor - 1).min(w - 2),
+                        (to.1 + corridor - 1).min(h - 2),
+                    ),
+                );
+            }
+        }
+
+        // crates left lying around the rooms
+        for tile in tiles.iter_mut() {
+            if matches!(tile, Tile::None) && chance(rng, 0.02 * params.density) {
+                *tile = movable();
+            }
+        }
+
+        finish(&mut tiles, params);
+        tiles
+    }
+}
+
+// random noise smoothed by a few cellular automaton steps into winding caves
+pub struct Caves;
+
+impl MapGenerator for Caves {
+    fn generate(&self, params: &MapParams, rng: &mut dyn RngCore) -> Vec<Tile> {
+        let (w, h) = (params.w, params.h);
+        let fill = (0.45 * params.density).clamp(0.0, 0.55);
+        let mut rock: Vec<bool> = (0..w * h).map(|_| chance(rng, fill)).collect();
+
+        for _ in 0..5 {
+            let prev = rock.clone();
+            for x in 1..w - 1 {
+                for y in 1..h - 1 {
+                    let mut n = 0;
+                    for z in x - 1..=x + 1 {
+                        for t in y - 1..=y + 1 {
+                            if (z, t) != (x, y) && prev[z + t * w] {
+                                n += 1;
+                            }
+                        }
+                    }
+                    rock[x + y * w] = n >= 5 || (prev[x + y * w] && n >= 4);
+                }
+            }
+        }
+
+        let mut tiles: Vec<Tile> = rock
+            .iter()
+            .map(|&r| if r { wall() } else { Tile::None })
+            .collect();
+
+        // loose rubble along the cave walls
+        for x in 1..w - 1 {
+            for y in 1..h - 1 {
+                let open = [(x - 1, y), (x + 1, y), (x, y - 1), (x, y + 1)]
+                    .iter()
+                    .any(|&(z, t)| !rock[z + t * w]);
+                if rock[x + y * w] && open && chance(rng, 0.08) {
+                    tiles[x + y * w] = movable();
+                }
+            }
+        }
+
+        finish(&mut tiles, params);
+        tiles
+    }
+}
+
+// a grid of streets between walled buildings with a door or two each
+pub struct City;
+
+impl City {
+    const BLOCK: usize = 28;
+    const STREET: usize = 5;
+}
+
+impl MapGenerator for City {
+    fn generate(&self, params: &MapParams, rng: &mut dyn RngCore) -> Vec<Tile> {
+        let (w, h) = (params.w, params.h);
+        let mut tiles = vec![Tile::None; w * h];
+
+        for bx in (0..w).step_by(Self::BLOCK) {
+            for by in (0..h).step_by(Self::BLOCK) {
+                let (x0, y0) = (bx + Self::STREET, by + Self::STREET);
+                let (x1, y1) = (
+                    (bx + Self::BLOCK - 1).min(w - 2),
+                    (by + Self::BLOCK - 1).min(h - 2),
+                );
+                if x1 < x0 + 4 || y1 < y0 + 4 {
+                    continue;
+                }
+
+                // empty lots are parks with some cover
+                if !chance(rng, 0.8 * params.density) {
+                    for x in x0..=x1 {
+                        for y in y0..=y1 {
+                            if chance(rng, 0.04) {
+                                tiles[x + y * w] = movable();
+                            }
+                        }
+                    }
+                    continue;
+                }
+
+                for x in x0..=x1 {
+                    for y in y0..=y1 {
+                        if x == x0 || x == x1 || y == y0 || y == y1 {
+                            tiles[x + y * w] = wall();
+                        } else if chance(rng, 0.03) {
+                            tiles[x + y * w] = movable();
+                        }
+                    }
+                }
+
+                // doors two tiles wide on one or two random sides
+                for _ in 0..rng.gen_range(1..=2) {
+                    let (dx, dy) = match rng.gen_range(0..4) {
+                        0 => (rng.gen_range(x0 + 1..x1 - 1), y0),
+                        1 => (rng.gen_range(x0 + 1..x1 - 1), y1),
+                        2 => (x0, rng.gen_range(y0 + 1..y1 - 1)),
+                        _ => (x1, rng.gen_range(y0 + 1..y1 - 1)),
+                    };
+                    let (ex, ey) = if dy == y0 || dy == y1 {
+                        (dx + 1, dy)
+                    } else {
+                        (dx, dy + 1)
+                    };
+                    tiles[dx + dy * w] = Tile::Door(Door::closed());
+                    tiles[ex + ey * w] = Tile::Door(Door::closed());
+                }
+            }
+        }
+
+        // wrecks blocking the streets
+        for tile in tiles.iter_mut() {
+            if matches!(tile, Tile::None) && chance(rng, 0.01 * params.density) {
+                *tile = movable();
+            }
+        }
+
+        finish(&mut tiles, params);
+        tiles
+    }
+}
+
+// mostly open ground with small clumps of cover and a few ponds
+pub struct Field;
+
+impl MapGenerator for Field {
+    fn generate(&self, params: &MapParams, rng: &mut dyn RngCore) -> Vec<Tile> {
+        let (w, h) = (params.w, params.h);
+        let mut tiles = vec![Tile::None; w * h];
+
+        for _ in 0..w * h / 4000 {
+            let (cx, cy) = (rng.gen_range(1..w - 1), rng.gen_range(1..h - 1));
+            let r = rng.gen_range(2..=5) as i32;
+            for y in (cy as i32 - r).max(1)..(cy as i32 + r).min(h as i32 - 1) {
+                for x in (cx as i32 - r).max(1)..(cx as i32 + r).min(w as i32 - 1) {
+                    let (dx, dy) = (x - cx as i32, y - cy as i32);
+                    if dx * dx + dy * dy <= r * r {
+                        tiles[x as usize + y as usize * w] = Tile::Water;
+                    }
+                }
+            }
+        }
+
+        let clumps = ((w * h) as f32 / 150.0 * params.density) as usize;
+        for _ in 0..clumps {
+            let (mut x, mut y) = (rng.gen_range(1..w - 1), rng.gen_range(1..h - 1));
+            let tile = if chance(rng, 0.6) { movable() } else { wall() };
+            for _ in 0..rng.gen_range(1..=4) {
+                tiles[x + y * w] = tile.clone();
+                match rng.gen_range(0..4) {
+                    0 => x = (x + 1).min(w - 2),
+                    1 => x = (x - 1).max(1),
+                    2 => y = (y + 1).min(h - 2),
+                    _ => y = (y - 1).max(1),
+                }
+            }
+        }
+
+        finish(&mut tiles, params);
+        tiles
+    }
+}
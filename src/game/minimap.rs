@@ -0,0 +1,274 @@
+use super::*;
+
+// tiles shown around the player on the corner minimap
+pub const MINIMAP_RANGE: i32 = 48;
+
+// ticks between uploads of the terrain texture
+const REFRESH: i32 = 10;
+
+// largest texture side, bigger maps get a window of this size that follows the player
+const WINDOW: i32 = 1024;
+
+// explored terrain baked into a texture with one pixel per tile
+pub struct Minimap {
+    // texture size and the tile at its top left corner
+    w: i32,
+    h: i32,
+    origin: (i32, i32),
+    // every explored tile, the window is refilled from it when it moves
+    memory: Chunks<[u8; 4]>,
+    pixels: Vec<u8>,
+    texture: Option<wgpu::Texture>,
+    dirty: bool,
+    pub full: bool,
+}
+
+impl Minimap {
+    pub fn new(w: i32, h: i32) -> Self {
+        let (tw, th) = if w <= WINDOW && h <= WINDOW {
+            (w, h)
+        } else {
+            (WINDOW, WINDOW)
+        };
+        Minimap {
+            w: tw,
+            h: th,
+            origin: (0, 0),
+            memory: Chunks::new(w, h, [0; 4]),
+            pixels: vec![0; (tw * th * 4) as usize],
+            texture: None,
+            dirty: true,
+            full: false,
+        }
+    }
+    // keeps the player in the middle half of the window
+    fn follow(&mut self, (px, py): (i32, i32)) {
+        let (ox, oy) = self.origin;
+        let margin = self.w.min(self.h) / 4;
+        let inside = px - ox >= margin
+            && py - oy >= margin
+            && ox + self.w - px > margin
+            && oy + self.h - py > margin;
+        if inside || (self.w == self.memory.w && self.h == self.memory.h) {
+            return;
+        }
+
+        self.origin = (
+            (px - self.w / 2).clamp(0, self.memory.w - self.w),
+            (py - self.h / 2).clamp(0, self.memory.h - self.h),
+        );
+        for y in 0..self.h {
+            for x in 0..self.w {
+                let idx = ((x + y * self.w) * 4) as usize;
+                let color = self.memory[(self.origin.0 + x, self.origin.1 + y)];
+                self.pixels[idx..idx + 4].copy_from_slice(&color);
+            }
+        }
+        self.dirty = true;
+    }
+    fn color(theme: &Theme, tile: &Tile) -> [u8; 4] {
+        let [r, g, b] = match tile {
+            Tile::Wall(_) => theme.wall.shade(0.65),
+            Tile::MovableWall(_) => theme.movable_wall.shade(0.65),
+            Tile::Door(_) => theme.door.shade(0.65),
+            Tile::Water => theme.water.shade(0.65),
+            Tile::Spikes => theme.spikes.shade(0.65),
+            Tile::Barricade(_) => theme.barricade.shade(0.65),
+            // chests are drawn on top so they can disappear
+            Tile::None | Tile::Chest(_) => theme.floor.shade(0.65),
+        };
+        [r, g, b, 255]
+    }
+    // repaints the tiles in sight and uploads the texture every few ticks
+    pub fn update(
+        &mut self,
+        app: &App,
+        theme: &Theme,
+        map: &Map,
+        fog: &Fog,
+        (px, py): (i32, i32),
+        frame: i32,
+    ) {
+        self.follow((px, py));
+
+        let (ox, oy) = self.origin;
+        for x in (px - SIGHT).max(0)..=(px + SIGHT).min(map.w - 1) {
+            for y in (py - SIGHT).max(0)..=(py + SIGHT).min(map.h - 1) {
+                if fog.visible(x, y) {
+                    let color = Self::color(theme, &map.tiles[(x, y)]);
+                    self.memory[(x, y)] = color;
+                    if x >= ox && y >= oy && x < ox + self.w && y < oy + self.h {
+                        let idx = ((x - ox + (y - oy) * self.w) * 4) as usize;
+                        self.pixels[idx..idx + 4].copy_from_slice(&color);
+                    }
+                    self.dirty = true;
+                }
+            }
+        }
+
+        if !self.dirty || (frame % REFRESH != 0 && self.texture.is_some()) {
+            return;
+        }
+        self.dirty = false;
+
+        let window = app.main_window();
+        let device = window.device();
+        let texture = self.texture.get_or_insert_with(|| {
+            wgpu::TextureBuilder::new()
+                .size([self.w as u32, self.h as u32])
+                .format(wgpu::TextureFormat::Rgba8UnormSrgb)
+                .usage(wgpu::TextureUsages::COPY_DST | wgpu::TextureUsages::TEXTURE_BINDING)
+                .build(device)
+        });
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("minimap"),
+        });
+        texture.upload_data(device, &mut encoder, &self.pixels);
+        window.queue().submit([encoder.finish()]);
+    }
+    pub fn render(
+        &self,
+        draw: &Draw,
+        state: &State,
+        map: &Map,
+        fog: &Fog,
+        player: (i32, i32),
+        frame: i32,
+    ) {
+        let s = state.scale();
+
+        if self.full {
+            draw.rect()
+                .w_h(state.w, state.h)
+                .color(state.theme.background.rgba(200));
+
+            // the longer side of the map gets the full size
+            let size = (state.w.min(state.h) - 160.0 * s).max(100.0);
+            let long = self.w.max(self.h) as f32;
+            let area = Rect::from_w_h(size * self.w as f32 / long, size * self.h as f32 / long);
+            self.draw_area(
+                draw,
+                state,
+                map,
+                fog,
+                player,
+                frame,
+                area,
+                [
+                    self.origin.0,
+                    self.origin.1,
+                    self.origin.0 + self.w,
+                    self.origin.1 + self.h,
+                ],
+            );
+
+            draw.pixtext(
+                state.lang.get("hud.map"),
+                [0.0, area.h() * 0.5 + 40.0 * s],
+                19 * 2 * s as u32,
+                (0, 0),
+                state.font.clone(),
+            )
+            .color(state.theme.text.rgba(255));
+        } else {
+            // below the food bar in the top right corner
+            let size = (200.0 * s).min(state.h * 0.25).floor();
+            let area = Rect::from_w_h(size, size).top_right_of(
+                Rect::from_w_h(state.w, state.h)
+                    .pad_right(12.0 * s)
+                    .pad_top(100.0 * s),
+            );
+
+            // keep the window inside the texture so edges don't smear
+            let d = MINIMAP_RANGE * 2;
+            let (ox, oy) = self.origin;
+            let x0 = (player.0 - MINIMAP_RANGE).clamp(ox, ox + (self.w - d).max(0));
+            let y0 = (player.1 - MINIMAP_RANGE).clamp(oy, oy + (self.h - d).max(0));
+
+            draw.rect()
+                .xy(area.xy())
+                .wh(area.wh() + 4.0 * s)
+                .color(state.theme.bar.rgba(255));
+            self.draw_area(
+                draw,
+                state,
+                map,
+                fog,
+                player,
+                frame,
+                area,
+                [x0, y0, x0 + d, y0 + d],
+            );
+        }
+    }
+    // draws the tiles in `[x0, y0, x1)` stretched over `area` with markers on top
+    fn draw_area(
+        &self,
+        draw: &Draw,
+        state: &State,
+        map: &Map,
+        fog: &Fog,
+        player: (i32, i32),
+        frame: i32,
+        area: Rect,
+        [x0, y0, x1, y1]: [i32; 4],
+    ) {
+        let Some(ref texture) = self.texture else {
+            return;
+        };
+
+        let (theme, s) = (&state.theme, state.scale());
+        let (w, h) = (self.w as f32, self.h as f32);
+        let nearest = wgpu::SamplerBuilder::new()
+            .mag_filter(wgpu::FilterMode::Nearest)
+            .min_filter(wgpu::FilterMode::Nearest)
+            .into_descriptor();
+
+        // texture coordinates start in the bottom left
+        let (ox, oy) = (self.origin.0 as f32, self.origin.1 as f32);
+        draw.sampler(nearest)
+            .texture(texture)
+            .xy(area.xy())
+            .wh(area.wh())
+            .area(Rect::from_corners(
+                pt2((x0 as f32 - ox) / w, 1.0 - (y1 as f32 - oy) / h),
+                pt2((x1 as f32 - ox) / w, 1.0 - (y0 as f32 - oy) / h),
+            ));
+
+        let tile = area.w() / (x1 - x0) as f32;
+        let marker = |x: i32, y: i32, size: f32, color: Rgba8| {
+            if x < x0 || x >= x1 || y < y0 || y >= y1 {
+                return;
+            }
+            draw.rect()
+                .x_y(
+                    (area.left() + (x - x0) as f32 * tile + tile * 0.5).round(),
+                    (area.top() - (y - y0) as f32 * tile - tile * 0.5).round(),
+                )
+                .w_h(size.max(tile), size.max(tile))
+                .color(color);
+        };
+
+        for &(x, y) in &map.chests {
+            if let Tile::Chest(ref chest) = map.tiles[(x, y)] {
+                if fog.explored(x, y) {
+                    marker(x, y, 3.0 * s, chest.ty.color(theme, frame).rgba(255));
+                }
+            }
+        }
+        for bomb in &map.bombs {
+            marker(bomb.x, bomb.y, 3.0 * s, theme.bomb.rgba(255));
+        }
+        for turret in &map.turrets {
+            marker(turret.x, turret.y, 3.0 * s, theme.turret.rgba(255));
+        }
+        if let Some(ref pos) = map.boss.pos {
+            let pulse = theme
+                .boss
+                .mix([255, 255, 255], 0.3 + (frame as f32 / 10.0).sin() * 0.3);
+            marker(pos.x, pos.y, 6.0 * s, pulse.rgba(255));
+        }
+        marker(player.0, player.1, 4.0 * s, theme.player.rgba(255));
+    }
+}
@@ -5,25 +5,45 @@ mod bomb;
 mod bullet;
 mod cam;
 mod chest;
+mod chunks;
+mod effects;
 mod emp;
 mod enemy;
+mod fog;
+mod light;
 mod map;
+mod mapfile;
+mod mapgen;
+mod minimap;
 mod pathfind;
 mod player;
+mod popup;
 mod progress;
+mod reach;
 mod turret;
 mod util;
+mod world;
 
 pub use animation::*;
 pub use bomb::*;
 pub use bullet::*;
 pub use cam::*;
 pub use chest::*;
+pub use chunks::*;
+pub use effects::*;
 pub use emp::*;
 pub use enemy::*;
+pub use fog::*;
+pub use light::*;
 pub use map::*;
+pub use mapfile::*;
+pub use mapgen::*;
+pub use minimap::*;
 pub use pathfind::*;
 pub use player::*;
+pub use popup::*;
 pub use progress::*;
+pub use reach::*;
 pub use turret::*;
 pub use util::*;
+pub use world::*;
@@ -2,72 +2,273 @@ use super::*;
 
 pub const MOB_FOV: usize = 30;
 
-pub fn mark(map: &Map, state: &mut [u8], x: usize, y: usize, offset: (i32, i32)) {
-    let ax = (x as i32 - MOB_FOV as i32 / 2) + offset.0;
-    let ay = (y as i32 - MOB_FOV as i32 / 2) + offset.1;
+// the flow field covers this far around the player, past where zombies move so paths can bend around
+const RADIUS: i32 = MOB_FOV as i32 + 18;
+const SIDE: i32 = 2 * RADIUS + 1;
 
-    if x > 0 && state[(x - 1) + y * MOB_FOV] != 1 && map.pass[(ax - 1, ay)] {
-        state[(x - 1) + y * MOB_FOV] = 2;
-    }
-    if x < MOB_FOV - 1 && state[(x + 1) + y * MOB_FOV] != 1 && map.pass[(ax + 1, ay)] {
-        state[(x + 1) + y * MOB_FOV] = 2;
-    }
-    if y > 0 && state[x + (y - 1) * MOB_FOV] != 1 && map.pass[(ax, ay - 1)] {
-        state[x + (y - 1) * MOB_FOV] = 2;
-    }
-    if y < MOB_FOV - 1 && state[x + (y + 1) * MOB_FOV] != 1 && map.pass[(ax, ay + 1)] {
-        state[x + (y + 1) * MOB_FOV] = 2;
+const UNREACHED: u32 = u32::MAX;
+
+// shoving a movable wall takes the step and a wait after it, a little over that
+// so zombies rather walk around one when the way is about as long
+pub const PUSH_COST: u32 = 3;
+
+// what it takes a zombie to get onto `(x, y)`, nothing if it can't. other zombies are
+// in the way only for a moment, doors and barricades cost the hits to break them.
+// movable walls cost a push, whether one goes from where the zombie is is `can_push`
+pub fn step_cost(map: &Map, (x, y): (i32, i32)) -> Option<u32> {
+    let free = map.pass[(x, y)] || map.enemies[(x, y)].is_some();
+    match map.tiles[(x, y)] {
+        Tile::Wall(_) => None,
+        Tile::MovableWall(_) => Some(PUSH_COST),
+        Tile::Door(Door {
+            open: false, hp, ..
+        })
+        | Tile::Barricade(Barricade { hp }) => Some(1 + hp.max(0) as u32),
+        Tile::Water => free.then_some(2),
+        _ => free.then_some(1),
     }
+}
 
-    state[x + y * MOB_FOV] = 1;
+// whether a zombie at `from` can shove the movable wall at `to` one further, like the
+// player does walls only slide over bare floor, and never onto the player
+pub fn can_push(map: &Map, from: (i32, i32), to: (i32, i32), player: (i32, i32)) -> bool {
+    let behind = (2 * to.0 - from.0, 2 * to.1 - from.1);
+    matches!(map.tiles[to], Tile::MovableWall(_))
+        && behind != player
+        && map.pass[behind]
+        && matches!(map.tiles[behind], Tile::None)
 }
 
-pub fn pathfind(map: &Map, from: (i32, i32), to: (i32, i32)) -> (i32, i32) {
-    let mut state = [0u8; MOB_FOV * MOB_FOV];
-    let mut poss = [(0i32, 0i32); MOB_FOV * 4 * 4];
+// a dijkstra map from the player, built once a tick and shared by every zombie
+pub struct FlowField {
+    // top left corner of the window in map tiles
+    x0: i32,
+    y0: i32,
+    // the player the field leads to
+    target: (i32, i32),
+    dist: Vec<u32>,
+    // `step_cost` of every tile in the window, 0 where zombies can't go
+    cost: Vec<u8>,
+    // tiles by distance modulo the number of buckets, steps never cost more than that
+    buckets: Vec<Vec<u32>>,
+}
+
+const BUCKETS: usize = 16;
 
-    let q = (
-        MOB_FOV as i32 / 2 + from.0 - to.0,
-        MOB_FOV as i32 / 2 + from.1 - to.1,
-    );
+impl FlowField {
+    pub fn new() -> Self {
+        FlowField {
+            x0: 0,
+            y0: 0,
+            target: (0, 0),
+            dist: vec![UNREACHED; (SIDE * SIDE) as usize],
+            cost: vec![0; (SIDE * SIDE) as usize],
+            buckets: vec![vec![]; BUCKETS],
+        }
+    }
+    fn index(&self, x: i32, y: i32) -> Option<usize> {
+        let (wx, wy) = (x - self.x0, y - self.y0);
+        (wx >= 0 && wy >= 0 && wx < SIDE && wy < SIDE).then(|| (wx + wy * SIDE) as usize)
+    }
+    // cost of the cheapest way from `(x, y)` to the player, if there's one inside the window
+    pub fn dist(&self, x: i32, y: i32) -> Option<u32> {
+        self.index(x, y)
+            .map(|i| self.dist[i])
+            .filter(|&d| d != UNREACHED)
+    }
+    pub fn update(&mut self, map: &Map, (px, py): (i32, i32)) {
+        (self.x0, self.y0) = (px - RADIUS, py - RADIUS);
+        self.target = (px, py);
+        self.dist.fill(UNREACHED);
+        // off the map reads as wall, so the edge needs no check of its own
+        for wy in 0..SIDE {
+            for wx in 0..SIDE {
+                let cost = step_cost(map, (self.x0 + wx, self.y0 + wy));
+                self.cost[(wx + wy * SIDE) as usize] = cost.unwrap_or(0) as u8;
+            }
+        }
 
-    mark(map, &mut state, MOB_FOV / 2, MOB_FOV / 2, to);
+        // the player may stand where zombies can't, on a turret that was just built
+        let start = self.index(px, py).unwrap();
+        self.cost[start] = 1;
+        self.dist[start] = 0;
+        self.buckets[0].push(start as u32);
 
-    for tries in 0..MOB_FOV * 4 {
-        let mut idx = 0;
+        // dijkstra with a bucket queue, costs are small so `d` only ever looks a few buckets ahead
+        let (mut d, mut left) = (0, 1);
+        while left > 0 {
+            let bucket = d as usize % BUCKETS;
+            while let Some(i) = self.buckets[bucket].pop() {
+                left -= 1;
+                let i = i as i32;
+                if self.dist[i as usize] != d {
+                    continue;
+                }
+                let (x, y) = (self.x0 + i % SIDE, self.y0 + i / SIDE);
+                let movable = matches!(map.tiles[(x, y)], Tile::MovableWall(_));
 
-        for x in 0..MOB_FOV {
-            for y in 0..MOB_FOV {
-                if state[x + y * MOB_FOV] == 2 {
-                    poss[idx] = (x as i32, y as i32);
-                    idx += 1;
+                // a zombie on the neighbour pays for getting onto this tile, a movable
+                // wall here only costs a push if it can go the way the zombie comes from
+                for (dx, dy) in DIRECTIONS {
+                    let Some(j) = self.index(x + dx, y + dy) else {
+                        continue;
+                    };
+                    if self.cost[j] == 0
+                        || movable && !can_push(map, (x + dx, y + dy), (x, y), (px, py))
+                    {
+                        continue;
+                    }
+                    let cost = self.cost[i as usize] as u32;
+                    if d + cost < self.dist[j] {
+                        self.dist[j] = d + cost;
+                        self.buckets[(d + cost) as usize % BUCKETS].push(j as u32);
+                        left += 1;
+                    }
                 }
             }
+            d += 1;
         }
-        if idx == 0 {
-            return (0, 0);
-        }
-        let mut best_k = 0;
-        let mut best_d = 1024;
-
-        for k in 0..idx {
-            let pos = poss[k];
-            let d = dist(q.0, q.1, pos.0, pos.1);
-
-            if d <= 1 {
-                return (
-                    pos.0 - MOB_FOV as i32 / 2 + to.0,
-                    pos.1 - MOB_FOV as i32 / 2 + to.1,
-                );
-            }
-            if d < best_d {
-                best_d = d;
-                best_k = k;
-            }
-            let best = poss[best_k];
-            mark(map, &mut state, best.0 as usize, best.1 as usize, to);
-        }
+    }
+    // the neighbour a zombie at `(x, y)` should take towards the player, counting what it takes
+    // to get onto it and skipping ones another zombie stands on. doors and barricades on the
+    // way come back too, to be broken, and movable walls to be pushed
+    pub fn step(&self, map: &Map, (x, y): (i32, i32)) -> Option<(i32, i32)> {
+        let here = self.dist(x, y).unwrap_or(UNREACHED);
+        DIRECTIONS
+            .iter()
+            .map(|&(dx, dy)| (x + dx, y + dy))
+            .filter_map(|p| {
+                let i = self.index(p.0, p.1)?;
+                (self.dist[i] < here).then(|| (self.dist[i] + self.cost[i] as u32, p))
+            })
+            .filter(|&(_, p)| {
+                map.pass[p] || map.tiles[p].breakable() || can_push(map, (x, y), p, self.target)
+            })
+            .min_by_key(|&(d, _)| d)
+            .map(|(_, p)| p)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn map(src: &str) -> Map {
+        Map::new(parse_map(src).unwrap())
     }
 
-    (0, 0)
+    fn zombie(map: &mut Map, (x, y): (i32, i32)) {
+        map.pass[(x, y)] = false;
+        map.enemies[(x, y)] = Some(Enemy {
+            uid: map.spawned,
+            hp: 1,
+            mhp: 1,
+            last: 0,
+            cd: 19,
+            slowed: 0,
+            immobilized: false,
+            ty: EnemyType::Zombie,
+        });
+        map.spawned += 1;
+    }
+
+    fn flow(map: &Map) -> FlowField {
+        let mut flow = FlowField::new();
+        flow.update(map, map.start);
+        flow
+    }
+
+    #[test]
+    fn walls_and_the_edge_keep_zombies_out() {
+        let map = map("#####\n\
+                       #@..#\n\
+                       #.#.#\n\
+                       #####\n");
+        let flow = flow(&map);
+
+        assert_eq!(flow.dist(1, 1), Some(0));
+        assert_eq!(flow.dist(3, 2), Some(3));
+        assert_eq!(flow.dist(2, 2), None);
+        assert_eq!(flow.dist(0, 0), None);
+        assert_eq!(flow.dist(-3, -3), None);
+        assert_eq!(flow.dist(1000, 1000), None);
+
+        assert_eq!(flow.step(&map, (3, 2)), Some((3, 1)));
+        assert_eq!(flow.step(&map, (-3, -3)), None);
+    }
+
+    #[test]
+    fn doors_and_barricades_cost_their_hp() {
+        let map = map("########\n\
+                       #@.D.=.#\n\
+                       ########\n");
+        let flow = flow(&map);
+
+        let door = flow.dist(3, 1).unwrap();
+        assert_eq!(flow.dist(4, 1), Some(door + 1 + DOOR_HP as u32));
+        let barricade = flow.dist(5, 1).unwrap();
+        assert_eq!(flow.dist(6, 1), Some(barricade + 1 + BARRICADE_HP as u32));
+
+        // they come back as the step, to be broken
+        assert_eq!(flow.step(&map, (4, 1)), Some((3, 1)));
+        assert_eq!(flow.step(&map, (6, 1)), Some((5, 1)));
+    }
+
+    #[test]
+    fn zombies_go_around_a_door_until_it_is_worn_down() {
+        let mut map = map("#######\n\
+                           #@.D..#\n\
+                           #.###.#\n\
+                           #.....#\n\
+                           #######\n");
+        let mut flow = flow(&map);
+        assert_eq!(flow.step(&map, (5, 1)), Some((5, 2)));
+
+        map.chip(3, 1, DOOR_HP - 1);
+        flow.update(&map, map.start);
+        assert_eq!(flow.step(&map, (5, 1)), Some((4, 1)));
+    }
+
+    #[test]
+    fn sealed_off_zombies_have_no_step() {
+        let mut map = map("#######\n\
+                           #@.#..#\n\
+                           #######\n");
+        zombie(&mut map, (5, 1));
+        let flow = flow(&map);
+
+        assert_eq!(flow.dist(5, 1), None);
+        assert_eq!(flow.step(&map, (5, 1)), None);
+    }
+
+    #[test]
+    fn movable_walls_in_the_way_get_pushed() {
+        let mut map = map("#######\n\
+                           #@..+.#\n\
+                           #######\n");
+        zombie(&mut map, (5, 1));
+        let flow = flow(&map);
+
+        assert_eq!(flow.dist(5, 1), Some(flow.dist(4, 1).unwrap() + PUSH_COST));
+        assert_eq!(flow.step(&map, (5, 1)), Some((4, 1)));
+    }
+
+    #[test]
+    fn walls_that_cannot_move_block_the_way() {
+        // the player right behind one wall, a chest behind the other
+        let mut map = map("#######\n\
+                           #.@+..#\n\
+                           #.#####\n\
+                           #...+.#\n\
+                           #######\n");
+        spawn_chest(&mut map, 0, 3, 3, ChestType::Ammo);
+        zombie(&mut map, (4, 1));
+        zombie(&mut map, (5, 3));
+        let flow = flow(&map);
+
+        assert!(!can_push(&map, (4, 1), (3, 1), map.start));
+        assert_eq!(flow.step(&map, (4, 1)), None);
+        assert!(!can_push(&map, (5, 3), (4, 3), map.start));
+        assert_eq!(flow.step(&map, (5, 3)), None);
+    }
 }
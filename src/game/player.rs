@@ -174,6 +174,13 @@ impl Player {
 
         p
     }
+    // adds up to `n` of a stat without going past its cap, gives back how much it went up
+    pub fn top_up(&mut self, stat: Stat, max: Stat, n: i32) -> i32 {
+        let (stat, max) = (stat as usize, max as usize);
+        let before = self.stats[stat];
+        self.stats[stat] = std::cmp::min(self.stats[max], before + n);
+        self.stats[stat] - before
+    }
 }
 
 #[repr(i32)]
@@ -187,24 +194,26 @@ pub enum Character {
     LiShen,
 }
 
-pub fn make_moves(player: &mut Player, keys: &Vec<Key>, map: &mut Map, state: &State, frame: i32) {
-    let Some(&last_key) = keys.last() else { return };
-
-    let (dx, dy) = match last_key {
-        _ if last_key as u32 == state.storage.hotkeys[Hotkey::Up as usize] => (0, -1),
-        _ if last_key as u32 == state.storage.hotkeys[Hotkey::Right as usize] => (1, 0),
-        _ if last_key as u32 == state.storage.hotkeys[Hotkey::Down as usize] => (0, 1),
-        _ if last_key as u32 == state.storage.hotkeys[Hotkey::Left as usize] => (-1, 0),
-        _ => {
-            return;
-        }
-    };
+pub fn make_moves(
+    player: &mut Player,
+    dir: Option<usize>,
+    map: &mut Map,
+    state: &State,
+    frame: i32,
+) {
+    let Some(dir) = dir else { return };
+    let (dx, dy) = DIRECTIONS[dir];
 
     let (mut px, mut py) = (player.x, player.y);
 
+    // pushing a wall or wading through water takes twice as long
+    let slow = matches!(
+        map.tiles[(px + dx, py + dy)],
+        Tile::MovableWall(_) | Tile::Water
+    ) || matches!(map.tiles[(px, py)], Tile::Water);
     if frame
         < player[LastMove]
-            + (if matches!(map.tiles[(px + dx, py + dy)], Tile::MovableWall(_)) {
+            + (if slow {
                 2 * player[CdMove]
             } else {
                 player[CdMove]
@@ -214,7 +223,7 @@ pub fn make_moves(player: &mut Player, keys: &Vec<Key>, map: &mut Map, state: &S
     }
 
     match map.tiles[(px + dx, py + dy)] {
-        Tile::None => {
+        Tile::None | Tile::Water | Tile::Spikes | Tile::Door(Door { open: true, .. }) => {
             if map.pass[(px + dx, py + dy)] {
                 px += dx;
                 py += dy;
@@ -223,9 +232,18 @@ pub fn make_moves(player: &mut Player, keys: &Vec<Key>, map: &mut Map, state: &S
                 }
             }
         }
-        Tile::Wall(_) => {}
-        Tile::MovableWall(MovableWall { bullets }) => {
-            if map.pass[(px + 2 * dx, py + 2 * dy)] {
+        Tile::Wall(_) | Tile::Barricade(_) => {}
+        // walking into a closed door opens it
+        Tile::Door(ref mut door) => {
+            door.open = true;
+            map.pass[(px + dx, py + dy)] = true;
+            state.play(Sound::UiSwitch, 0.25);
+        }
+        Tile::MovableWall(MovableWall { hp }) => {
+            // walls only slide over bare floor
+            if map.pass[(px + 2 * dx, py + 2 * dy)]
+                && matches!(map.tiles[(px + 2 * dx, py + 2 * dy)], Tile::None)
+            {
                 px += dx;
                 py += dy;
                 if player.running {
@@ -233,7 +251,7 @@ pub fn make_moves(player: &mut Player, keys: &Vec<Key>, map: &mut Map, state: &S
                 }
 
                 map.tiles[(px, py)] = Tile::None;
-                map.tiles[(px + dx, py + dy)] = Tile::MovableWall(MovableWall { bullets });
+                map.tiles[(px + dx, py + dy)] = Tile::MovableWall(MovableWall { hp });
 
                 map.pass[(px, py)] = true;
                 map.pass[(px + dx, py + dy)] = false;
@@ -247,25 +265,32 @@ pub fn make_moves(player: &mut Player, keys: &Vec<Key>, map: &mut Map, state: &S
             }
 
             state.play(Sound::PickChest, 0.25);
-
-            match chest.ty {
-                ChestType::Ammo => player[Ammo] = std::cmp::min(player[MaxAmmo], player[Ammo] + 3),
-                ChestType::Bomb => player[Bomb] = std::cmp::min(player[MaxBomb], player[Bomb] + 1),
+            let food = |player: &mut Player| {
+                let gained = player.top_up(Food, MaxFood, 500_000);
+                (100.0 * gained as f32 / player[MaxFood] as f32).round() as i32
+            };
+            let gained = match chest.ty {
+                ChestType::Ammo => vec![(ChestType::Ammo, player.top_up(Ammo, MaxAmmo, 3))],
+                ChestType::Bomb => vec![(ChestType::Bomb, player.top_up(Bomb, MaxBomb, 1))],
                 ChestType::Turret => {
-                    player[Turret] = std::cmp::min(player[MaxTurret], player[Turret] + 1)
+                    vec![(ChestType::Turret, player.top_up(Turret, MaxTurret, 1))]
                 }
-                ChestType::Emp => player[Emp] = std::cmp::min(player[MaxEmp], player[Emp] + 1),
-                ChestType::Food => {
-                    player[Food] = std::cmp::min(player[MaxFood], player[Food] + 500_000)
-                }
-                ChestType::Rainbow => {
-                    player[Ammo] = std::cmp::min(player[MaxAmmo], player[Ammo] + 3);
-                    player[Bomb] = std::cmp::min(player[MaxBomb], player[Bomb] + 1);
-                    player[Turret] = std::cmp::min(player[MaxTurret], player[Turret] + 1);
-                    player[Emp] = std::cmp::min(player[MaxEmp], player[Emp] + 1);
-                    player[Food] = std::cmp::min(player[MaxFood], player[Food] + 500_000);
-                }
-            }
+                ChestType::Emp => vec![(ChestType::Emp, player.top_up(Emp, MaxEmp, 1))],
+                ChestType::Food => vec![(ChestType::Food, food(player))],
+                ChestType::Rainbow => vec![
+                    (ChestType::Ammo, player.top_up(Ammo, MaxAmmo, 3)),
+                    (ChestType::Bomb, player.top_up(Bomb, MaxBomb, 1)),
+                    (ChestType::Turret, player.top_up(Turret, MaxTurret, 1)),
+                    (ChestType::Emp, player.top_up(Emp, MaxEmp, 1)),
+                    (ChestType::Food, food(player)),
+                ],
+            };
+            map.events.push(GameEvent::Pickup {
+                x: px,
+                y: py,
+                ty: chest.ty.clone(),
+                gained: gained.into_iter().filter(|&(_, n)| n > 0).collect(),
+            });
 
             map.tiles[(px, py)] = Tile::None;
             map.pass[(px, py)] = true;
@@ -283,3 +308,46 @@ pub fn make_moves(player: &mut Player, keys: &Vec<Key>, map: &mut Map, state: &S
     player.x = px;
     player.y = py;
 }
+
+// food it takes to put up a barricade
+const BARRICADE_FOOD: i32 = 100_000;
+
+// opens or closes the door the player faces, or the first one next to them,
+// anywhere else it builds a barricade in that direction
+pub fn check_use(dir: Option<usize>, player: &mut Player, map: &mut Map, state: &State) {
+    let (px, py) = (player.x, player.y);
+    let next = |dir: usize| (px + DIRECTIONS[dir].0, py + DIRECTIONS[dir].1);
+    let door = dir
+        .filter(|&d| matches!(map.tiles[next(d)], Tile::Door(_)))
+        .or_else(|| (0..DIRECTIONS.len()).find(|&d| matches!(map.tiles[next(d)], Tile::Door(_))));
+
+    if let Some(d) = door {
+        let (x, y) = next(d);
+        let Tile::Door(ref mut door) = map.tiles[(x, y)] else {
+            unreachable!()
+        };
+        // a zombie or turret in the doorway keeps it open
+        if door.open && !map.pass[(x, y)] {
+            return;
+        }
+        door.open = !door.open;
+        map.pass[(x, y)] = door.open;
+        state.play(Sound::UiSwitch, 0.25);
+        return;
+    }
+
+    let Some(d) = dir else { return };
+    let (x, y) = next(d);
+    if player[Food] <= BARRICADE_FOOD
+        || !map.pass[(x, y)]
+        || !matches!(map.tiles[(x, y)], Tile::None)
+        || map.bombs.iter().any(|b| b.x == x && b.y == y)
+    {
+        return;
+    }
+
+    state.play(Sound::UseTurret, 0.3);
+    player[Food] -= BARRICADE_FOOD;
+    map.tiles[(x, y)] = Tile::Barricade(Barricade { hp: BARRICADE_HP });
+    map.pass[(x, y)] = false;
+}
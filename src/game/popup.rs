@@ -0,0 +1,113 @@
+use super::*;
+
+// transient text floating up from where something happened
+struct Popup {
+    x: f32,
+    y: f32,
+    text: String,
+    color: Color,
+    big: bool,
+    age: i32,
+    life: i32,
+}
+
+pub struct Popups {
+    list: Vec<Popup>,
+}
+
+impl Popups {
+    pub fn new() -> Self {
+        Popups { list: vec![] }
+    }
+    pub fn handle(&mut self, event: &GameEvent, state: &State) {
+        let theme = &state.theme;
+        let (x, y, text, color, big) = match *event {
+            GameEvent::Damage { x, y, amount } => {
+                (x as f32, y as f32, format!("{}", amount), theme.text, false)
+            }
+            // score shows up above the damage of the killing blow
+            GameEvent::Score { x, y, amount } => (
+                x as f32,
+                y as f32 - 0.6,
+                format!("+{}", amount),
+                theme.score,
+                amount >= 100,
+            ),
+            // a line per supply the chest topped up, at the cap it adds nothing and says so
+            GameEvent::Pickup {
+                x,
+                y,
+                ref ty,
+                ref gained,
+            } => {
+                let lang = &state.lang;
+                let (x, y) = (x as f32, y as f32);
+                if gained.is_empty() {
+                    let text = String::from(lang.get("pickup.full"));
+                    self.push(x, y, text, ty.color(theme, 0), false);
+                }
+                for (k, &(ref ty, n)) in gained.iter().enumerate() {
+                    let text = match ty {
+                        ChestType::Food => format!("+{}% {}", n, lang.get("pickup.food")),
+                        ChestType::Ammo => format!("+{} {}", n, lang.get("pickup.ammo")),
+                        ChestType::Bomb => format!("+{} {}", n, lang.get("pickup.bomb")),
+                        ChestType::Turret => format!("+{} {}", n, lang.get("pickup.turret")),
+                        ChestType::Emp | ChestType::Rainbow => {
+                            format!("+{} {}", n, lang.get("pickup.emp"))
+                        }
+                    };
+                    self.push(x, y - 0.6 * k as f32, text, ty.color(theme, 0), false);
+                }
+                return;
+            }
+            _ => return,
+        };
+
+        self.push(x, y, text, color, big);
+    }
+    fn push(&mut self, x: f32, y: f32, text: String, color: Color, big: bool) {
+        self.list.push(Popup {
+            x,
+            y,
+            text,
+            color,
+            big,
+            age: 0,
+            life: if big { 120 } else { 45 },
+        });
+    }
+    pub fn update(&mut self) {
+        self.list.retain_mut(|popup| {
+            popup.y -= 0.02;
+            popup.age += 1;
+            popup.age < popup.life
+        });
+    }
+    pub fn render(&self, draw: &Draw, state: &State, fog: &Fog, cam: &Camera, tile: f32) {
+        let s = state.scale() as u32;
+
+        for popup in &self.list {
+            let (x, y) = (popup.x.round() as i32, popup.y.round() as i32);
+            if x < 0 || y < 0 || !fog.visible(x, y) {
+                continue;
+            }
+
+            // fade out during the last third
+            let p = popup.age as f32 / popup.life as f32;
+            let alpha = (255.0 * ((1.0 - p) * 3.0).min(1.0)) as u8;
+
+            let [r, g, b] = popup.color;
+            draw.pixtext(
+                &popup.text,
+                [
+                    (popup.x - cam.x) * tile,
+                    (cam.y - popup.y) * tile + tile * 0.5,
+                ],
+                if popup.big { 19 * 2 * s } else { 19 * s },
+                (0, 0),
+                state.font.clone(),
+            )
+            .rgba8(r, g, b, alpha);
+        }
+    }
+}
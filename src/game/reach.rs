@@ -0,0 +1,89 @@
+use super::*;
+
+// movable walls can be pushed or blown up, only solid walls cut a region off
+fn open(tile: &Tile) -> bool {
+    !matches!(tile, Tile::Wall(_))
+}
+
+// tunnels every walled off pocket back to the start through the fewest walls
+pub fn connect(tiles: &mut [Tile], w: usize, h: usize, (sx, sy): (i32, i32)) {
+    // 0-1 bfs, stepping onto a wall costs one and open ground is free
+    let mut cost = vec![u32::MAX; w * h];
+    let mut from = vec![usize::MAX; w * h];
+    let mut queue = VecDeque::new();
+
+    let start = sx as usize + sy as usize * w;
+    cost[start] = 0;
+    queue.push_back(start);
+
+    while let Some(i) = queue.pop_front() {
+        let (x, y) = (i % w, i / w);
+        for (nx, ny) in [(x - 1, y), (x + 1, y), (x, y - 1), (x, y + 1)] {
+            // the border stays intact
+            if nx == 0 || ny == 0 || nx == w - 1 || ny == h - 1 {
+                continue;
+            }
+            let j = nx + ny * w;
+            let step = if open(&tiles[j]) { 0 } else { 1 };
+            if cost[i] + step < cost[j] {
+                cost[j] = cost[i] + step;
+                from[j] = i;
+                if step == 0 {
+                    queue.push_front(j);
+                } else {
+                    queue.push_back(j);
+                }
+            }
+        }
+    }
+
+    for i in 0..w * h {
+        // the border itself is never visited
+        if !open(&tiles[i]) || cost[i] == 0 || cost[i] == u32::MAX {
+            continue;
+        }
+        // walk back to the start region, once carved the path is free for everyone after
+        let mut k = i;
+        while cost[k] > 0 {
+            if !open(&tiles[k]) {
+                tiles[k] = Tile::None;
+            }
+            cost[k] = 0;
+            k = from[k];
+        }
+    }
+}
+
+// marks everything reachable from `(x, y)` that isn't already
+pub fn spread(reach: &mut Chunks<bool>, tiles: &Chunks<Tile>, (x, y): (i32, i32)) {
+    if reach[(x, y)] || !open(&tiles[(x, y)]) {
+        return;
+    }
+
+    let (w, h) = (reach.w, reach.h);
+    let mut stack = vec![(x, y)];
+    reach[(x, y)] = true;
+
+    while let Some((x, y)) = stack.pop() {
+        for (dx, dy) in [(-1, 0), (1, 0), (0, -1), (0, 1)] {
+            let (nx, ny) = (x + dx, y + dy);
+            if nx < 0 || ny < 0 || nx >= w || ny >= h {
+                continue;
+            }
+            if !reach[(nx, ny)] && open(&tiles[(nx, ny)]) {
+                reach[(nx, ny)] = true;
+                stack.push((nx, ny));
+            }
+        }
+    }
+}
+
+// a tile that just opened up joins the reachable ground it touches, with whatever lies behind it
+pub fn join(reach: &mut Chunks<bool>, tiles: &Chunks<Tile>, (x, y): (i32, i32)) {
+    let touching = [(-1, 0), (1, 0), (0, -1), (0, 1)]
+        .iter()
+        .any(|&(dx, dy)| reach[(x + dx, y + dy)]);
+    if touching {
+        spread(reach, tiles, (x, y));
+    }
+}
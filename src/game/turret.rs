@@ -13,7 +13,7 @@ pub struct Turret {
 }
 
 pub fn check_turret(
-    keys: &Vec<Key>,
+    dir: Option<usize>,
     player: &mut Player,
     map: &mut Map,
     state: &State,
@@ -35,17 +35,7 @@ pub fn check_turret(
         return;
     }
     let mut rng = thread_rng();
-    let direction = if let Some(&last_key) = keys.last() {
-        match last_key {
-            _ if last_key as u32 == state.storage.hotkeys[Hotkey::Up as usize] => 1,
-            _ if last_key as u32 == state.storage.hotkeys[Hotkey::Right as usize] => 2,
-            _ if last_key as u32 == state.storage.hotkeys[Hotkey::Down as usize] => 3,
-            _ if last_key as u32 == state.storage.hotkeys[Hotkey::Left as usize] => 0,
-            _ => rng.gen_range(0..=3),
-        }
-    } else {
-        rng.gen_range(0..=3)
-    };
+    let direction = dir.unwrap_or_else(|| rng.gen_range(0..=3));
 
     state.play(Sound::UseTurret, 0.3);
 
@@ -0,0 +1,269 @@
+use super::*;
+
+// side of the endless world, far enough out that nobody walks to the edge
+pub const ENDLESS: i32 = CHUNK * 512;
+
+// chunks around the player's chunk that are live, and how far one can be before it's put away
+const LIVE: i32 = 2;
+const KEEP: i32 = 4;
+
+// terrain streamed in around the player for the endless mode
+pub struct World {
+    generator: Box<dyn MapGenerator>,
+    live: HashSet<(i32, i32)>,
+    // terrain of chunks that went out of range, one byte per tile, anything on it is dropped
+    stash: HashMap<(i32, i32), Vec<u8>>,
+    // chunk the player was in when chunks were last streamed
+    center: Option<(i32, i32)>,
+}
+
+impl World {
+    pub fn new(generator: Box<dyn MapGenerator>) -> Self {
+        World {
+            generator,
+            live: HashSet::new(),
+            stash: HashMap::new(),
+            center: None,
+        }
+    }
+    // the live square of chunks in tiles
+    pub fn bounds(&self) -> [i32; 4] {
+        let (cx, cy) = self.center.unwrap_or_default();
+        [
+            ((cx - LIVE) * CHUNK).max(0),
+            ((cy - LIVE) * CHUNK).max(0),
+            ((cx + LIVE + 1) * CHUNK).min(ENDLESS),
+            ((cy + LIVE + 1) * CHUNK).min(ENDLESS),
+        ]
+    }
+    pub fn stream(&mut self, map: &mut Map, (px, py): (i32, i32)) {
+        let center = Chunks::<Tile>::chunk_of(px, py);
+        if self.center == Some(center) {
+            return;
+        }
+        self.center = Some(center);
+
+        let far: Vec<_> = self
+            .live
+            .iter()
+            .copied()
+            .filter(|&(x, y)| (x - center.0).abs().max((y - center.1).abs()) > KEEP)
+            .collect();
+        for chunk in far {
+            self.put_away(map, chunk);
+        }
+
+        let last = ENDLESS / CHUNK - 1;
+        let mut fresh = vec![];
+        for cy in (center.1 - LIVE).max(0)..=(center.1 + LIVE).min(last) {
+            for cx in (center.0 - LIVE).max(0)..=(center.0 + LIVE).min(last) {
+                if !self.live.contains(&(cx, cy)) {
+                    self.bring_in(map, (cx, cy));
+                    fresh.push((cx, cy));
+                }
+            }
+        }
+
+        // chunks meet at their gates but the rest of a seam may not line up, so reach
+        // spreads from the player and over the edges of the new chunks into what's reached
+        spread(&mut map.reach, &map.tiles, (px, py));
+        for &(cx, cy) in &fresh {
+            let (x0, y0, x1, y1) = (
+                cx * CHUNK,
+                cy * CHUNK,
+                (cx + 1) * CHUNK - 1,
+                (cy + 1) * CHUNK - 1,
+            );
+            for i in 0..CHUNK {
+                for p in [(x0 + i, y0), (x0 + i, y1), (x0, y0 + i), (x1, y0 + i)] {
+                    join(&mut map.reach, &map.tiles, p);
+                }
+            }
+        }
+
+        // fresh zombies and chests every time, they don't survive being put away
+        for (cx, cy) in fresh {
+            let (x0, y0) = (cx * CHUNK, cy * CHUNK);
+            let area = [x0, y0, x0 + CHUNK, y0 + CHUNK];
+            let mut open = 0;
+            for y in y0..y0 + CHUNK {
+                for x in x0..x0 + CHUNK {
+                    if map.reach[(x, y)] && map.pass[(x, y)] {
+                        open += 1;
+                    }
+                }
+            }
+            map.spawn_enemies(enemies_for(open), area, (px, py));
+            map.spawn_chests(chests_for(open), area);
+        }
+    }
+    // a fresh chunk, generated with a margin that's cut off so it has no border walls of its own
+    fn generate(&self) -> Vec<Tile> {
+        let side = CHUNK as usize + 2;
+        let params = MapParams {
+            clear_start: false,
+            ..MapParams::new(side, side)
+        };
+        let padded = self.generator.generate(&params, &mut thread_rng());
+
+        let mut tiles = Vec::with_capacity((CHUNK * CHUNK) as usize);
+        for y in 1..side - 1 {
+            tiles.extend_from_slice(&padded[1 + y * side..side - 1 + y * side]);
+        }
+        // a gate in the middle of every side lines up with the one on the chunk next to it, so
+        // generators that wall their edges off don't leave every chunk an island
+        let mid = CHUNK / 2;
+        let last = CHUNK - 1;
+        for (x, y) in [(mid, mid), (mid, 0), (mid, 1), (mid, last - 1), (mid, last)]
+            .into_iter()
+            .flat_map(|(x, y)| [(x, y), (y, x)])
+        {
+            tiles[(x + y * CHUNK) as usize] = Tile::None;
+        }
+        let c = CHUNK as usize;
+        connect(&mut tiles, c, c, (mid, mid));
+        tiles
+    }
+    fn bring_in(&mut self, map: &mut Map, (cx, cy): (i32, i32)) {
+        let tiles = match self.stash.remove(&(cx, cy)) {
+            Some(bytes) => bytes.into_iter().map(unpack).collect(),
+            None => self.generate(),
+        };
+
+        let (x0, y0) = (cx * CHUNK, cy * CHUNK);
+        for (i, tile) in tiles.into_iter().enumerate() {
+            let (x, y) = (x0 + i as i32 % CHUNK, y0 + i as i32 / CHUNK);
+            map.pass[(x, y)] = tile.walkable();
+            // nothing is reached until it's joined up with the player
+            map.reach[(x, y)] = false;
+            map.enemies[(x, y)] = None;
+            map.tiles[(x, y)] = tile;
+        }
+
+        // the start stays open like it does on a generated map
+        let (sx, sy) = map.start;
+        for x in x0..x0 + CHUNK {
+            for y in y0..y0 + CHUNK {
+                if dist(x, y, sx, sy) < 10 {
+                    map.tiles[(x, y)] = Tile::None;
+                    map.pass[(x, y)] = true;
+                }
+            }
+        }
+
+        self.live.insert((cx, cy));
+    }
+    fn put_away(&mut self, map: &mut Map, (cx, cy): (i32, i32)) {
+        let inside = |x: i32, y: i32| Chunks::<Tile>::chunk_of(x, y) == (cx, cy);
+
+        if map.boss.pos.as_ref().is_some_and(|p| inside(p.x, p.y)) {
+            map.boss.pos = None;
+        }
+        map.chests.retain(|&(x, y)| !inside(x, y));
+        map.turrets.retain(|t| !inside(t.x, t.y));
+        map.bombs.retain(|b| !inside(b.x, b.y));
+        map.bullets.retain(|b| !inside(b.x, b.y));
+
+        let (x0, y0) = (cx * CHUNK, cy * CHUNK);
+        let mut bytes = Vec::with_capacity((CHUNK * CHUNK) as usize);
+        for y in y0..y0 + CHUNK {
+            for x in x0..x0 + CHUNK {
+                bytes.push(pack(&map.tiles[(x, y)]));
+            }
+        }
+        self.stash.insert((cx, cy), bytes);
+
+        map.tiles.unload((cx, cy));
+        map.pass.unload((cx, cy));
+        map.reach.unload((cx, cy));
+        map.enemies.unload((cx, cy));
+        self.live.remove(&(cx, cy));
+    }
+}
+
+// chests are dropped along with the zombies and everything comes back whole, doors closed
+fn pack(tile: &Tile) -> u8 {
+    match tile {
+        Tile::Wall(_) => 1,
+        Tile::MovableWall(_) => 2,
+        Tile::Door(_) => 3,
+        Tile::Water => 4,
+        Tile::Spikes => 5,
+        Tile::Barricade(_) => 6,
+        Tile::None | Tile::Chest(_) => 0,
+    }
+}
+
+fn unpack(byte: u8) -> Tile {
+    match byte {
+        1 => Tile::Wall(Wall::whole()),
+        2 => Tile::MovableWall(MovableWall::whole()),
+        3 => Tile::Door(Door::closed()),
+        4 => Tile::Water,
+        5 => Tile::Spikes,
+        6 => Tile::Barricade(Barricade { hp: BARRICADE_HP }),
+        _ => Tile::None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // tiles of the live chunks the player could walk to, movable walls can be pushed or blown up
+    fn walkable_from(map: &Map, from: (i32, i32)) -> HashSet<(i32, i32)> {
+        let mut seen = HashSet::from([from]);
+        let mut stack = vec![from];
+        while let Some((x, y)) = stack.pop() {
+            for (dx, dy) in DIRECTIONS {
+                let p = (x + dx, y + dy);
+                if !matches!(map.tiles[p], Tile::Wall(_)) && seen.insert(p) {
+                    stack.push(p);
+                }
+            }
+        }
+        seen
+    }
+
+    fn check_reach(map: &Map, g: Generator) {
+        let open = walkable_from(map, map.start);
+        let [x0, y0, x1, y1] = map.bounds();
+        for y in y0..y1 {
+            for x in x0..x1 {
+                assert_eq!(
+                    map.reachable(x, y),
+                    open.contains(&(x, y)),
+                    "{:?} at ({}, {})",
+                    g,
+                    x,
+                    y
+                );
+                if map.enemies[(x, y)].is_some() || matches!(map.tiles[(x, y)], Tile::Chest(_)) {
+                    assert!(open.contains(&(x, y)), "{:?} spawned in a pocket", g);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn reach_follows_the_seams_between_chunks() {
+        for g in Generator::ALL {
+            let mut map = Map::endless(g.build());
+            check_reach(&map, g);
+
+            // a chunk over, the new column joins up with what's already there over the seam
+            let start = map.start;
+            let next = (0..CHUNK * CHUNK)
+                .map(|i| (start.0 + CHUNK + i % CHUNK, start.1 + i / CHUNK))
+                .find(|&(x, y)| map.reachable(x, y))
+                .unwrap();
+            map.stream(next);
+            check_reach(&map, g);
+
+            // everything around the start gets put away and comes back from the stash
+            map.stream((start.0 + 16 * CHUNK, start.1));
+            map.stream(start);
+            check_reach(&map, g);
+        }
+    }
+}
@@ -0,0 +1,543 @@
+use super::*;
+
+use std::{cell::RefCell, rc::Rc};
+
+// how far a stick has to lean to count as a d-pad press, and to let go again
+const STICK_PRESS: f32 = 0.5;
+const STICK_RELEASE: f32 = 0.3;
+
+// a physical control, keyboard keys and gamepad buttons both bind to actions
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Input {
+    Key(#[serde(with = "key_code")] Key),
+    Pad(Pad),
+}
+
+// keys are stored by their code like the old hotkey list
+mod key_code {
+    use super::*;
+    use serde::{de::Error, Deserializer, Serializer};
+    use std::result::Result;
+
+    pub fn serialize<S: Serializer>(key: &Key, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_u32(*key as u32)
+    }
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Key, D::Error> {
+        let code = u32::deserialize(d)?;
+        KEYS.get(code as usize)
+            .copied()
+            .ok_or_else(|| D::Error::custom("unknown key code"))
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Pad {
+    South,
+    East,
+    North,
+    West,
+    LeftBumper,
+    RightBumper,
+    LeftTrigger,
+    RightTrigger,
+    Select,
+    Start,
+    LeftStick,
+    RightStick,
+    DPadUp,
+    DPadDown,
+    DPadLeft,
+    DPadRight,
+}
+
+impl Input {
+    // held down to change what another input does, on its own it binds on release
+    pub fn is_modifier(self) -> bool {
+        matches!(
+            self,
+            Input::Key(
+                Key::LShift | Key::RShift | Key::LControl | Key::RControl | Key::LAlt | Key::RAlt
+            ) | Input::Pad(Pad::LeftBumper | Pad::RightBumper)
+        )
+    }
+    // the key a menu should treat this input as, the d-pad navigates and south confirms
+    pub fn key(self) -> Option<Key> {
+        match self {
+            Input::Key(k) => Some(k),
+            Input::Pad(Pad::DPadUp) => Some(Key::Up),
+            Input::Pad(Pad::DPadDown) => Some(Key::Down),
+            Input::Pad(Pad::DPadLeft) => Some(Key::Left),
+            Input::Pad(Pad::DPadRight) => Some(Key::Right),
+            Input::Pad(Pad::South) => Some(Key::Space),
+            Input::Pad(Pad::East) => Some(Key::Escape),
+            Input::Pad(Pad::Select) => Some(Key::S),
+            Input::Pad(_) => None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Action {
+    Up,
+    Left,
+    Down,
+    Right,
+
+    Shoot,
+    Bomb,
+    Turret,
+    Emp,
+    Use,
+
+    Run,
+    Pause,
+}
+
+impl Action {
+    pub const ALL: [Action; 11] = [
+        Action::Up,
+        Action::Left,
+        Action::Down,
+        Action::Right,
+        Action::Shoot,
+        Action::Bomb,
+        Action::Turret,
+        Action::Emp,
+        Action::Use,
+        Action::Run,
+        Action::Pause,
+    ];
+    // index into `DIRECTIONS` for the four movement actions
+    pub fn direction(self) -> Option<usize> {
+        match self {
+            Action::Left => Some(0),
+            Action::Up => Some(1),
+            Action::Right => Some(2),
+            Action::Down => Some(3),
+            _ => None,
+        }
+    }
+    fn defaults(self) -> [Option<Binding>; 2] {
+        let (key, pad) = match self {
+            Action::Up => (Key::Up, Pad::DPadUp),
+            Action::Left => (Key::Left, Pad::DPadLeft),
+            Action::Down => (Key::Down, Pad::DPadDown),
+            Action::Right => (Key::Right, Pad::DPadRight),
+            Action::Shoot => (Key::LControl, Pad::RightTrigger),
+            Action::Bomb => (Key::Q, Pad::South),
+            Action::Turret => (Key::E, Pad::LeftTrigger),
+            Action::Emp => (Key::R, Pad::North),
+            Action::Use => (Key::F, Pad::West),
+            Action::Run => (Key::W, Pad::LeftBumper),
+            Action::Pause => (Key::P, Pad::Start),
+        };
+        [
+            Some(Binding::new(Input::Key(key), None)),
+            Some(Binding::new(Input::Pad(pad), None)),
+        ]
+    }
+}
+
+// an input, optionally only while a modifier is held
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Binding {
+    pub input: Input,
+    pub modifier: Option<Input>,
+}
+
+impl Binding {
+    pub fn new(input: Input, modifier: Option<Input>) -> Self {
+        Binding { input, modifier }
+    }
+    fn active(&self, held: &HashSet<Input>) -> bool {
+        self.modifier.map_or(true, |m| held.contains(&m))
+    }
+    pub fn name(&self) -> String {
+        let name = |input: Input| match input {
+            Input::Key(k) => format!("{:?}", k),
+            Input::Pad(p) => format!("{:?}", p),
+        };
+        match self.modifier {
+            Some(m) => format!("{}+{}", name(m), name(self.input)),
+            None => name(self.input),
+        }
+    }
+}
+
+// a primary and a secondary binding per action, actions missing from storage keep their defaults
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct Bindings {
+    slots: HashMap<Action, [Option<Binding>; 2]>,
+}
+
+impl Bindings {
+    // the old hotkey list held key codes in this order
+    const LEGACY: [Action; 9] = [
+        Action::Up,
+        Action::Left,
+        Action::Down,
+        Action::Right,
+        Action::Shoot,
+        Action::Bomb,
+        Action::Turret,
+        Action::Emp,
+        Action::Run,
+    ];
+    // bindings from a save made before there were any, actions added since keep
+    // their defaults unless one of those is already taken
+    pub fn legacy(keys: &[u32]) -> Self {
+        let mut bindings = Bindings::default();
+        for (k, &action) in Bindings::LEGACY.iter().enumerate() {
+            let mut slots = action.defaults();
+            if let Some(&key) = keys.get(k).and_then(|&code| KEYS.get(code as usize)) {
+                slots[0] = Some(Binding::new(Input::Key(key), None));
+            }
+            bindings.slots.insert(action, slots);
+        }
+
+        let taken: Vec<_> = bindings
+            .slots
+            .values()
+            .flatten()
+            .flatten()
+            .copied()
+            .collect();
+        for action in Action::ALL {
+            if !Bindings::LEGACY.contains(&action) {
+                let slots = action.defaults().map(|b| b.filter(|b| !taken.contains(b)));
+                bindings.slots.insert(action, slots);
+            }
+        }
+        bindings
+    }
+    pub fn get(&self, action: Action) -> [Option<Binding>; 2] {
+        self.slots
+            .get(&action)
+            .copied()
+            .unwrap_or_else(|| action.defaults())
+    }
+    // the action a press triggers, a binding whose modifier is held wins over a plain one
+    pub fn action(&self, input: Input, held: &HashSet<Input>) -> Option<Action> {
+        let mut found = None;
+        for action in Action::ALL {
+            for binding in self.get(action).into_iter().flatten() {
+                if binding.input != input || !binding.active(held) {
+                    continue;
+                }
+                if binding.modifier.is_some() {
+                    return Some(action);
+                }
+                found = found.or(Some(action));
+            }
+        }
+        found
+    }
+    // like `action`, a plain binding isn't held while a combo on the same input is
+    pub fn held(&self, action: Action, held: &HashSet<Input>) -> bool {
+        self.get(action).into_iter().flatten().any(|binding| {
+            held.contains(&binding.input)
+                && binding.active(held)
+                && (binding.modifier.is_some() || !self.combo_held(binding.input, held))
+        })
+    }
+    // whether some action's combo on `input` has its modifier held
+    fn combo_held(&self, input: Input, held: &HashSet<Input>) -> bool {
+        Action::ALL.iter().any(|&action| {
+            self.get(action)
+                .into_iter()
+                .flatten()
+                .any(|b| b.input == input && b.modifier.is_some() && b.active(held))
+        })
+    }
+    // refuses a binding another action already uses and names that action
+    pub fn bind(
+        &mut self,
+        action: Action,
+        slot: usize,
+        binding: Option<Binding>,
+    ) -> std::result::Result<(), Action> {
+        if let Some(binding) = binding {
+            for other in Action::ALL {
+                let taken = self
+                    .get(other)
+                    .iter()
+                    .enumerate()
+                    .any(|(k, &b)| b == Some(binding) && (other != action || k != slot));
+                if taken {
+                    return Err(other);
+                }
+            }
+        }
+
+        let mut slots = self.get(action);
+        slots[slot] = binding;
+        self.slots.insert(action, slots);
+        Ok(())
+    }
+}
+
+// anything besides the window that produces presses and releases, polled every frame
+pub trait InputSource {
+    fn poll(&mut self, events: &mut Vec<(Input, bool)>);
+}
+
+// what's held down across the window and every source, fed each press and release as it
+// comes so bindings are read with the modifiers that were held at the time
+#[derive(Default)]
+pub struct Held {
+    inputs: HashSet<Input>,
+}
+
+impl Held {
+    // a press or release, the action a press triggers
+    pub fn feed(&mut self, bindings: &Bindings, input: Input, down: bool) -> Option<Action> {
+        if down {
+            self.inputs.insert(input);
+            bindings.action(input, &self.inputs)
+        } else {
+            self.inputs.remove(&input);
+            None
+        }
+    }
+    // polls every source and feeds what came in, in order
+    pub fn drain(
+        &mut self,
+        sources: &mut [Box<dyn InputSource>],
+        bindings: &Bindings,
+    ) -> Vec<(Input, bool, Option<Action>)> {
+        let mut events = vec![];
+        for source in sources {
+            source.poll(&mut events);
+        }
+        events
+            .into_iter()
+            .map(|(input, down)| (input, down, self.feed(bindings, input, down)))
+            .collect()
+    }
+    pub fn holds(&self, bindings: &Bindings, action: Action) -> bool {
+        bindings.held(action, &self.inputs)
+    }
+}
+
+// every connected controller, they all act as one
+pub struct Gamepads {
+    gilrs: Option<gilrs::Gilrs>,
+    // left stick leaning up, down, left, right
+    stick: [bool; 4],
+}
+
+impl Gamepads {
+    pub fn new() -> Self {
+        Gamepads {
+            gilrs: gilrs::Gilrs::new().ok(),
+            stick: [false; 4],
+        }
+    }
+    fn button(button: gilrs::Button) -> Option<Pad> {
+        use gilrs::Button;
+        Some(match button {
+            Button::South => Pad::South,
+            Button::East => Pad::East,
+            Button::North => Pad::North,
+            Button::West => Pad::West,
+            Button::LeftTrigger => Pad::LeftBumper,
+            Button::RightTrigger => Pad::RightBumper,
+            Button::LeftTrigger2 => Pad::LeftTrigger,
+            Button::RightTrigger2 => Pad::RightTrigger,
+            Button::Select => Pad::Select,
+            Button::Start => Pad::Start,
+            Button::LeftThumb => Pad::LeftStick,
+            Button::RightThumb => Pad::RightStick,
+            Button::DPadUp => Pad::DPadUp,
+            Button::DPadDown => Pad::DPadDown,
+            Button::DPadLeft => Pad::DPadLeft,
+            Button::DPadRight => Pad::DPadRight,
+            _ => return None,
+        })
+    }
+    // the stick is reported as d-pad presses so it moves the same way
+    fn lean(&mut self, events: &mut Vec<(Input, bool)>, dir: usize, value: f32) {
+        let pad = [Pad::DPadUp, Pad::DPadDown, Pad::DPadLeft, Pad::DPadRight][dir];
+        if !self.stick[dir] && value > STICK_PRESS {
+            self.stick[dir] = true;
+            events.push((Input::Pad(pad), true));
+        } else if self.stick[dir] && value < STICK_RELEASE {
+            self.stick[dir] = false;
+            events.push((Input::Pad(pad), false));
+        }
+    }
+}
+
+impl InputSource for Gamepads {
+    fn poll(&mut self, events: &mut Vec<(Input, bool)>) {
+        use gilrs::{Axis, EventType};
+
+        let Some(ref mut gilrs) = self.gilrs else {
+            return;
+        };
+
+        let mut axes = vec![];
+        while let Some(gilrs::Event { event, .. }) = gilrs.next_event() {
+            match event {
+                EventType::ButtonPressed(button, _) => {
+                    if let Some(pad) = Self::button(button) {
+                        events.push((Input::Pad(pad), true));
+                    }
+                }
+                EventType::ButtonReleased(button, _) => {
+                    if let Some(pad) = Self::button(button) {
+                        events.push((Input::Pad(pad), false));
+                    }
+                }
+                EventType::AxisChanged(axis @ (Axis::LeftStickX | Axis::LeftStickY), value, _) => {
+                    axes.push((axis, value));
+                }
+                _ => {}
+            }
+        }
+
+        // y points up on the stick
+        for (axis, value) in axes {
+            if axis == Axis::LeftStickY {
+                self.lean(events, 0, value);
+                self.lean(events, 1, -value);
+            } else {
+                self.lean(events, 2, -value);
+                self.lean(events, 3, value);
+            }
+        }
+    }
+}
+
+// scripted presses and releases, drives the game the same way a controller would. clones
+// share the queue, so one can be handed to the context and the other keep scripting
+#[derive(Clone)]
+pub struct Synthetic {
+    queue: Rc<RefCell<VecDeque<(Input, bool)>>>,
+}
+
+impl Synthetic {
+    pub fn new() -> Self {
+        Synthetic {
+            queue: Rc::new(RefCell::new(VecDeque::new())),
+        }
+    }
+    pub fn press(&self, input: Input) {
+        self.queue.borrow_mut().push_back((input, true));
+    }
+    pub fn release(&self, input: Input) {
+        self.queue.borrow_mut().push_back((input, false));
+    }
+}
+
+impl InputSource for Synthetic {
+    fn poll(&mut self, events: &mut Vec<(Input, bool)>) {
+        events.extend(self.queue.borrow_mut().drain(..));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a frame of the context's input, the actions the presses trigger
+    fn frame(held: &mut Held, sources: &mut [Box<dyn InputSource>], b: &Bindings) -> Vec<Action> {
+        held.drain(sources, b)
+            .into_iter()
+            .filter_map(|(_, _, action)| action)
+            .collect()
+    }
+
+    #[test]
+    fn keys_and_buttons_drive_the_same_actions() {
+        let b = Bindings::default();
+        let mut held = Held::default();
+        let input = Synthetic::new();
+        let mut sources: Vec<Box<dyn InputSource>> = vec![Box::new(input.clone())];
+
+        input.press(Input::Key(Key::Q));
+        input.press(Input::Pad(Pad::RightTrigger));
+        assert_eq!(
+            frame(&mut held, &mut sources, &b),
+            [Action::Bomb, Action::Shoot]
+        );
+        assert!(held.holds(&b, Action::Shoot));
+
+        input.release(Input::Pad(Pad::RightTrigger));
+        assert!(frame(&mut held, &mut sources, &b).is_empty());
+        assert!(!held.holds(&b, Action::Shoot));
+        assert!(frame(&mut held, &mut sources, &b).is_empty());
+    }
+
+    #[test]
+    fn modifier_combos_win_while_held() {
+        let mut b = Bindings::default();
+        let shift = Input::Key(Key::LShift);
+        b.bind(
+            Action::Emp,
+            1,
+            Some(Binding::new(Input::Key(Key::Q), Some(shift))),
+        )
+        .unwrap();
+        let mut held = Held::default();
+        let input = Synthetic::new();
+        let mut sources: Vec<Box<dyn InputSource>> = vec![Box::new(input.clone())];
+
+        // the first q goes in before shift within the same frame
+        input.press(Input::Key(Key::Q));
+        input.release(Input::Key(Key::Q));
+        input.press(shift);
+        input.press(Input::Key(Key::Q));
+        assert_eq!(
+            frame(&mut held, &mut sources, &b),
+            [Action::Bomb, Action::Emp]
+        );
+        assert!(held.holds(&b, Action::Emp));
+        assert!(!held.holds(&b, Action::Bomb));
+
+        input.release(shift);
+        frame(&mut held, &mut sources, &b);
+        assert!(!held.holds(&b, Action::Emp));
+        assert!(held.holds(&b, Action::Bomb));
+    }
+
+    #[test]
+    fn every_source_is_drained_in_order() {
+        let b = Bindings::default();
+        let mut held = Held::default();
+        let (pad, keys) = (Synthetic::new(), Synthetic::new());
+        let mut sources: Vec<Box<dyn InputSource>> =
+            vec![Box::new(pad.clone()), Box::new(keys.clone())];
+
+        keys.press(Input::Key(Key::Q));
+        pad.press(Input::Pad(Pad::RightTrigger));
+        pad.release(Input::Pad(Pad::RightTrigger));
+        let events: Vec<_> = held
+            .drain(&mut sources, &b)
+            .into_iter()
+            .map(|(input, down, _)| (input, down))
+            .collect();
+        assert_eq!(
+            events,
+            [
+                (Input::Pad(Pad::RightTrigger), true),
+                (Input::Pad(Pad::RightTrigger), false),
+                (Input::Key(Key::Q), true),
+            ]
+        );
+        assert!(held.holds(&b, Action::Bomb));
+        assert!(!held.holds(&b, Action::Shoot));
+    }
+
+    #[test]
+    fn bindings_taken_elsewhere_are_refused() {
+        let mut b = Bindings::default();
+        let q = Some(Binding::new(Input::Key(Key::Q), None));
+
+        assert_eq!(b.bind(Action::Shoot, 0, q), Err(Action::Bomb));
+        assert_eq!(b.bind(Action::Bomb, 1, q), Err(Action::Bomb));
+        assert_eq!(b.bind(Action::Bomb, 0, q), Ok(()));
+        b.bind(Action::Bomb, 0, None).unwrap();
+        assert_eq!(b.bind(Action::Shoot, 0, q), Ok(()));
+    }
+}
@@ -0,0 +1,91 @@
+use super::*;
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Language {
+    English,
+    Spanish,
+    German,
+    Russian,
+}
+
+impl Language {
+    pub const ALL: [Language; 4] = [
+        Language::English,
+        Language::Spanish,
+        Language::German,
+        Language::Russian,
+    ];
+    fn table(self) -> &'static str {
+        match self {
+            Language::English => include_str!("../assets/lang/en.json"),
+            Language::Spanish => include_str!("../assets/lang/es.json"),
+            Language::German => include_str!("../assets/lang/de.json"),
+            Language::Russian => include_str!("../assets/lang/ru.json"),
+        }
+    }
+    // the next language `d` steps along that can be shown, this one if none can
+    pub fn cycle(self, d: i32) -> Language {
+        let n = Language::ALL.len() as i32;
+        let idx = Language::ALL.iter().position(|&l| l == self).unwrap_or(0) as i32;
+        (1..n)
+            .map(|k| Language::ALL[(idx + d * k).rem_euclid(n) as usize])
+            .find(|&l| Lang::load(l).usable())
+            .unwrap_or(self)
+    }
+}
+
+#[derive(Deserialize)]
+struct Table {
+    name: String,
+    // path of a font with the glyphs m5x7 lacks, relative to the game directory
+    font: Option<String>,
+    strings: HashMap<String, String>,
+}
+
+impl Table {
+    fn parse(language: Language) -> Self {
+        json::from_str(language.table()).expect("Invalid language table")
+    }
+}
+
+// ui strings keyed by id, anything the table leaves out comes from english
+pub struct Lang {
+    pub name: String,
+    font: Option<String>,
+    strings: HashMap<String, String>,
+    fallback: HashMap<String, String>,
+}
+
+impl Lang {
+    pub fn load(language: Language) -> Self {
+        let table = Table::parse(language);
+        let fallback = if language == Language::English {
+            HashMap::new()
+        } else {
+            Table::parse(Language::English).strings
+        };
+
+        Lang {
+            name: table.name,
+            font: table.font,
+            strings: table.strings,
+            fallback,
+        }
+    }
+    // unknown ids show up as themselves so they are easy to spot
+    pub fn get<'a>(&'a self, id: &'a str) -> &'a str {
+        self.strings
+            .get(id)
+            .or_else(|| self.fallback.get(id))
+            .map_or(id, |s| s.as_str())
+    }
+    // the fallback font if this language needs one and it could be read
+    pub fn font(&self) -> Option<Font> {
+        let raw = std::fs::read(self.font.as_ref()?).ok()?;
+        Font::from_bytes(raw).ok()
+    }
+    // a language whose font isn't there would show up as blanks, so it's left out
+    pub fn usable(&self) -> bool {
+        self.font.is_none() || self.font().is_some()
+    }
+}
@@ -0,0 +1,14 @@
+#![allow(warnings)]
+mod audio;
+mod context;
+mod game;
+mod input;
+mod lang;
+mod re;
+mod screen;
+mod storage;
+mod theme;
+
+pub use re::*;
+
+pub static VERSION: &str = "1.1.2";
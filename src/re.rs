@@ -1,9 +1,13 @@
 use super::*;
 
+pub use audio::*;
 pub use context::*;
 pub use game::*;
+pub use input::*;
+pub use lang::*;
 pub use screen::*;
 pub use storage::*;
+pub use theme::*;
 
 pub use std::{collections::*, f32::consts::PI, ops::*, time::*, io::{Read, Write, Cursor}, thread::*};
 
@@ -13,6 +17,7 @@ pub use nannou::{
     prelude::*,
     rand::*,
     text::*,
+    winit,
 };
 
 pub use serde::{Deserialize, Serialize};
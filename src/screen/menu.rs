@@ -1,37 +1,45 @@
 use super::*;
 
-static NAMES: &[&str] = &["Joshua", "Anne", "Andrew", "Matthew", "Megan", "Li-Shen"];
+// ids into the language table, one entry per character
+static NAMES: &[&str] = &[
+    "name.joshua",
+    "name.anne",
+    "name.andrew",
+    "name.matthew",
+    "name.megan",
+    "name.lishen",
+];
 
 static DESC: &[&str] = &[
-    "Olympic Runner",
-    "Devil Pyromaniac",
-    "Arms Dealer",
-    "Hobbyist Engineer",
-    "Scientist at NASA",
-    "Monk of the Jade Temple",
+    "desc.joshua",
+    "desc.anne",
+    "desc.andrew",
+    "desc.matthew",
+    "desc.megan",
+    "desc.lishen",
 ];
 
 static BONUSES: &[&[&str]] = &[
-    &["increased movement speed"],
+    &["bonus.joshua.speed"],
     &[
-        "start with 1 max ammo",
-        "+3 max bombs",
-        "+1 bomb radius",
-        "-1s bomb fuse time",
+        "bonus.anne.ammo",
+        "bonus.anne.bombs",
+        "bonus.anne.radius",
+        "bonus.anne.fuse",
     ],
-    &["+1 bullet damage", "+20% chance to not consume ammo"],
-    &["doubled turret duration"],
-    &["+3 EMP range", "EMP immobilizes for half the duration"],
-    &["+50% max food", "increased luck"],
+    &["bonus.andrew.damage", "bonus.andrew.ammo"],
+    &["bonus.matthew.turrets"],
+    &["bonus.megan.range", "bonus.megan.stun"],
+    &["bonus.lishen.food", "bonus.lishen.luck"],
 ];
 
 static ACHIEVEMENTS: &[&str] = &[
-    "Do nothing",
-    "Achieve 1000 score using only bombs",
-    "Kill 150 enemies in one game",
-    "Kill 50 enemies using only turrets in one game",
-    "Achieve 3000 score",
-    "Achieve 1000 score without killing any monsters",
+    "unlock.joshua",
+    "unlock.anne",
+    "unlock.andrew",
+    "unlock.matthew",
+    "unlock.megan",
+    "unlock.lishen",
 ];
 
 pub struct Menu {
@@ -74,23 +82,45 @@ impl Menu {
     pub fn render(&self, app: &App, draw: &Draw, state: &State) {
         let m = app.mouse.position();
         let alpha = 255 - ((self.frame as f32 / 40.0).sin().abs() * 200.0) as u8;
+        // the corner prompts stop blinking under the cursor
+        let prompt = |hovered: bool| {
+            if hovered {
+                state.theme.accent.rgba(255)
+            } else {
+                state.theme.text.rgba(alpha)
+            }
+        };
+
         draw.pixtext(
-            "press SPACE to begin",
+            state.lang.get("menu.begin"),
             [state.w * 0.5 - 132.0, -state.h * 0.5 + 32.0],
             19,
             (0, 0),
             state.font.clone(),
         )
-        .rgba8(255, 255, 255, alpha);
+        .color(prompt(Self::on_begin(state, m)));
 
         draw.pixtext(
-            "press S to open settings",
+            state.lang.get("menu.settings"),
             [-state.w * 0.5 + 156.0, -state.h * 0.5 + 32.0],
             19,
             (0, 0),
             state.font.clone(),
         )
-        .rgba8(255, 255, 255, alpha);
+        .color(prompt(Self::on_settings(state, m)));
+
+        draw.pixtext(
+            format!(
+                "{}  < {} >",
+                state.lang.get("menu.map"),
+                state.storage.map.name(&state.lang)
+            ),
+            [0.0, -state.h * 0.5 + 32.0],
+            19,
+            (0, 0),
+            state.font.clone(),
+        )
+        .color(prompt(Self::on_map(state, m)));
 
         draw.pixtext(
             VERSION,
@@ -99,7 +129,7 @@ impl Menu {
             (0, 0),
             state.font.clone(),
         )
-        .rgba8(255, 255, 255, 255);
+        .color(state.theme.text.rgba(255));
 
         for k in 0..self.chars.len() {
             let d = (k as f32 - self.achs).abs();
@@ -110,13 +140,13 @@ impl Menu {
             let br = max(255.0 - d * 128.0, 128.0) as u8;
 
             draw.pixtext(
-                NAMES[k],
+                state.lang.get(NAMES[k]),
                 [0.0, 0.0 + (self.achs - k as f32) * state.h * 0.3],
                 19 * 2,
                 (0, 0),
                 state.font.clone(),
             )
-            .rgba8(br, br, br, 255);
+            .color(state.theme.text.shade(br as f32 / 255.0).rgba(255));
         }
 
         let a = self.achs.floor();
@@ -130,31 +160,31 @@ impl Menu {
 
         if !self.unlocked[aidx] {
             draw.pixtext(
-                "LOCKED",
+                state.lang.get("menu.locked"),
                 [state.w * 0.25, 64.0],
                 19 * 2,
                 (0, 0),
                 state.font.clone(),
             )
-            .rgba8(255, 0, 0, abr);
+            .color(state.theme.accent.rgba(abr));
 
             draw.pixtext(
-                "To unlock you must",
+                state.lang.get("menu.unlock"),
                 [-state.w * 0.25, 0.0],
                 19 * 2,
                 (0, 0),
                 state.font.clone(),
             )
-            .rgba8(255, 0, 0, abr);
+            .color(state.theme.accent.rgba(abr));
 
             draw.pixtext(
-                ACHIEVEMENTS[aidx],
+                state.lang.get(ACHIEVEMENTS[aidx]),
                 [-state.w * 0.25, -48.0],
                 19,
                 (0, 0),
                 state.font.clone(),
             )
-            .rgba8(255, 0, 0, abr);
+            .color(state.theme.accent.rgba(abr));
         } else {
             draw.pixtext(
                 format!("{}", state.storage.highscore),
@@ -163,16 +193,16 @@ impl Menu {
                 (0, 0),
                 state.font.clone(),
             )
-            .rgba8(255, 255, 255, abr);
+            .color(state.theme.text.rgba(abr));
 
             draw.pixtext(
-                "HIGHSCORE",
+                state.lang.get("menu.highscore"),
                 [-state.w * 0.25, -32.0],
                 19,
                 (0, 0),
                 state.font.clone(),
             )
-            .rgba8(255, 255, 255, abr);
+            .color(state.theme.text.rgba(abr));
         }
 
         {
@@ -181,64 +211,64 @@ impl Menu {
             let br = max(255.0 - d * 128.0, 128.0) as u8;
 
             draw.pixtext(
-                "Select your identity",
+                state.lang.get("menu.select"),
                 [0.0, 0.0 + (self.achs + 1 as f32) * state.h * 0.3],
                 19 * 2,
                 (0, 0),
                 state.font.clone(),
             )
-            .rgba8(br, br, br, 255);
+            .color(state.theme.text.shade(br as f32 / 255.0).rgba(255));
         }
 
         draw.pixtext(
-            DESC[aidx],
+            state.lang.get(DESC[aidx]),
             [state.w * 0.25, 0.0],
             19 * 2,
             (0, 0),
             state.font.clone(),
         )
-        .rgba8(255, 255, 255, abr);
+        .color(state.theme.text.rgba(abr));
 
         for k in 0..BONUSES[aidx].len() {
             draw.pixtext(
-                BONUSES[aidx][k],
+                state.lang.get(BONUSES[aidx][k]),
                 [state.w * 0.25, -48.0 - k as f32 * 32.0],
                 19,
                 (0, 0),
                 state.font.clone(),
             )
-            .rgba8(255, 255, 255, abr);
+            .color(state.theme.text.rgba(abr));
         }
 
         //
 
         if !self.unlocked[bidx] {
             draw.pixtext(
-                "LOCKED",
+                state.lang.get("menu.locked"),
                 [state.w * 0.25, 64.0],
                 19 * 2,
                 (0, 0),
                 state.font.clone(),
             )
-            .rgba8(255, 0, 0, bbr);
+            .color(state.theme.accent.rgba(bbr));
 
             draw.pixtext(
-                "To unlock you must",
+                state.lang.get("menu.unlock"),
                 [-state.w * 0.25, 0.0],
                 19 * 2,
                 (0, 0),
                 state.font.clone(),
             )
-            .rgba8(255, 0, 0, bbr);
+            .color(state.theme.accent.rgba(bbr));
 
             draw.pixtext(
-                ACHIEVEMENTS[bidx],
+                state.lang.get(ACHIEVEMENTS[bidx]),
                 [-state.w * 0.25, -48.0],
                 19,
                 (0, 0),
                 state.font.clone(),
             )
-            .rgba8(255, 0, 0, bbr);
+            .color(state.theme.accent.rgba(bbr));
         } else {
             draw.pixtext(
                 format!("{}", state.storage.highscore),
@@ -247,36 +277,36 @@ impl Menu {
                 (0, 0),
                 state.font.clone(),
             )
-            .rgba8(255, 255, 255, bbr);
+            .color(state.theme.text.rgba(bbr));
 
             draw.pixtext(
-                "HIGHSCORE",
+                state.lang.get("menu.highscore"),
                 [-state.w * 0.25, -32.0],
                 19,
                 (0, 0),
                 state.font.clone(),
             )
-            .rgba8(255, 255, 255, bbr);
+            .color(state.theme.text.rgba(bbr));
         }
 
         draw.pixtext(
-            DESC[bidx],
+            state.lang.get(DESC[bidx]),
             [state.w * 0.25, 0.0],
             19 * 2,
             (0, 0),
             state.font.clone(),
         )
-        .rgba8(255, 255, 255, bbr);
+        .color(state.theme.text.rgba(bbr));
 
         for k in 0..BONUSES[bidx].len() {
             draw.pixtext(
-                BONUSES[bidx][k],
+                state.lang.get(BONUSES[bidx][k]),
                 [state.w * 0.25, -48.0 - k as f32 * 32.0],
                 19,
                 (0, 0),
                 state.font.clone(),
             )
-            .rgba8(255, 255, 255, bbr);
+            .color(state.theme.text.rgba(bbr));
         }
 
     }
@@ -286,7 +316,8 @@ impl Menu {
 
         self.next.take()
     }
-    pub fn pressed(&mut self, app: &App, state: &mut State, k: Key) {
+    pub fn pressed(&mut self, app: &App, state: &mut State, input: Input) {
+        let Some(k) = input.key() else { return };
         match k {
             Key::Up if self.chs > 0 => {
                 state.play(Sound::UiSwitch, 0.75);
@@ -296,19 +327,9 @@ impl Menu {
                 state.play(Sound::UiSwitch, 0.75);
                 self.chs += 1;
             }
-            Key::Space if self.unlocked[self.chs] => {
-                self.intro.take().unwrap().fade(2000);
-                //state.play(Sound::UiSwitch, 1.0);
-                let playing = Some(Box::new(Screen::Playing(Playing::new(
-                    self.chars[self.chs],
-                    state
-                ))));
-                if !state.storage.achievements[&Achievement::Tutorial].state {
-                    self.next = Some(Box::new(Screen::Tutorial(Tutorial::new(playing))));
-                } else {
-                    self.next = playing;
-                }
-            }
+            Key::Left => self.cycle(state, -1),
+            Key::Right => self.cycle(state, 1),
+            Key::Space => self.begin(state),
             Key::S => {
                 state.play(Sound::UiSwitch, 0.75);
                 self.next = Some(Box::new(Screen::Settings(Settings::new())));
@@ -319,12 +340,69 @@ impl Menu {
             _ => {}
         }
     }
-    pub fn released(&mut self, app: &App, state: &mut State, k: Key) {}
+    pub fn released(&mut self, app: &App, state: &mut State, input: Input) {}
     pub fn mpressed(&mut self, app: &App, state: &mut State, mb: MouseButton) {
         let m = app.mouse.position();
 
-        if m.x < -state.w * 0.5 + 164.0 && m.y < -state.h * 0.5 + 32.0 {
+        if Self::on_settings(state, m) {
             self.next = Some(Box::new(Screen::Settings(Settings::new())));
+        } else if Self::on_begin(state, m) {
+            self.begin(state);
+        } else if Self::on_map(state, m) {
+            self.cycle(state, if mb == MouseButton::Right { -1 } else { 1 });
+        } else if let Some(k) = self.hovered(state, m) {
+            // a second click on the selected character starts the game
+            if k == self.chs {
+                self.begin(state);
+            } else {
+                state.play(Sound::UiSwitch, 0.75);
+                self.chs = k;
+            }
+        }
+    }
+    fn begin(&mut self, state: &mut State) {
+        if !self.unlocked[self.chs] || self.next.is_some() {
+            return;
+        }
+        if let Some(intro) = self.intro.take() {
+            intro.fade(state, 2000);
+        }
+        //state.play(Sound::UiSwitch, 1.0);
+        let playing = Some(Box::new(Screen::Playing(Playing::new(
+            self.chars[self.chs],
+            state
+        ))));
+        if !state.storage.achievements[&Achievement::Tutorial].state {
+            self.next = Some(Box::new(Screen::Tutorial(Tutorial::new(playing))));
+        } else {
+            self.next = playing;
         }
     }
+    // steps through the map generators and bundled maps, the choice is kept for the next run
+    fn cycle(&mut self, state: &mut State, d: i32) {
+        let all = MapSource::all();
+        let idx = all
+            .iter()
+            .position(|&m| m == state.storage.map)
+            .unwrap_or(0);
+        state.storage.map = all[(idx as i32 + d).rem_euclid(all.len() as i32) as usize];
+        state.storage.save();
+        state.play(Sound::UiSwitch, 0.75);
+    }
+    fn on_settings(state: &State, m: Vec2) -> bool {
+        m.x < -state.w * 0.5 + 164.0 && m.y < -state.h * 0.5 + 32.0
+    }
+    fn on_begin(state: &State, m: Vec2) -> bool {
+        m.x > state.w * 0.5 - 264.0 && m.y < -state.h * 0.5 + 32.0
+    }
+    fn on_map(state: &State, m: Vec2) -> bool {
+        m.x.abs() < 160.0 && m.y < -state.h * 0.5 + 32.0
+    }
+    // the character name under the cursor
+    fn hovered(&self, state: &State, m: Vec2) -> Option<usize> {
+        (0..self.chars.len()).find(|&k| {
+            let y = (self.achs - k as f32) * state.h * 0.3;
+            Rect::from_x_y_w_h(0.0, y, 480.0, 64.0).contains(m)
+        })
+    }
 }
@@ -3,102 +3,128 @@ use super::*;
 pub struct Playing {
     frame: i32,
     map: Map,
+    fog: Fog,
+    // the way to the player for every zombie, rebuilt each tick
+    flow: FlowField,
+    minimap: Minimap,
+    effects: Effects,
+    popups: Popups,
     cam: Camera,
     player: Player,
-    movement: Vec<Key>,
-    ambient: Option<Sink>,
+    // held movement inputs with their direction, the last one wins
+    movement: Vec<(Input, usize)>,
+    // turret held, it's placed when released or when a direction is pressed
+    placing: bool,
+    paused: bool,
+    music: Option<Music>,
     next: Option<Box<Screen>>
 }
 
 impl Playing {
     pub fn new(ch: Character, state: &State) -> Self {
-        let ambient = state.play_get(Sound::Ambient, 0.2);
-        let map = Map::new();
-        let (x, y) = (map.w / 2, map.h / 2);
+        let storage = &state.storage;
+        let map = storage.map.build(storage.map_size, storage.map_shape);
+        let (x, y) = map.start;
+        let fog = Fog::new(map.w, map.h);
         Playing {
             frame: 0,
             player: Player::new(ch, x, y),
+            minimap: Minimap::new(map.w, map.h),
+            effects: Effects::new(),
+            popups: Popups::new(),
             map,
+            fog,
+            flow: FlowField::new(),
             cam: Camera::new(x as f32, y as f32),
             movement: Vec::with_capacity(4),
-            ambient: Some(ambient),
+            placing: false,
+            paused: false,
+            music: Some(Music::new()),
             next: None,
         }
     }
+    // the bomb and emp animations were tuned with 3.1415 rather than pi
+    #[allow(clippy::approx_constant)]
     pub fn render(&self, app: &App, draw: &Draw, state: &State) {
-        const HFOV: f32 = 24.0;
-        const VFOV: f32 = 12.0;
-        const TILE_WIDTH: f32 = 48.0;
-
-        for x in max(self.cam.x - HFOV, 0.0)..=min(self.cam.x + HFOV, (self.map.w - 1) as f32) {
-            for y in max(self.cam.y - VFOV, 0.0)..=min(self.cam.y + VFOV, (self.map.h - 1) as f32) {
-                let (vx, vy) = (self.cam.x - x as f32, self.cam.y - y as f32);
-
-                // (my)TODO: optimize later for light table for quick access
-                let alpha = {
-                    let mut dsq = (vx * vx) + (vy * vy);
-                    if self.frame < 60 {
-                        dsq *= ((self.frame - 30) as f32 / 30.0).clamp(0.001, 1.0).inv();
-                    }
-                    let mut sum = (255 - 2 * dsq as i32).clamp(0, 255);
-                    for bomb in &self.map.bombs {
-                        if self.frame < bomb.start + bomb.duration - 20 {
-                            let dsq = (bomb.x - x) * (bomb.x - x) + (bomb.y - y) * (bomb.y - y);
-                            sum += (255 - 16 * dsq).clamp(0, 255);
-                        } else {
-                            let dsq = (bomb.x - x) * (bomb.x - x) + (bomb.y - y) * (bomb.y - y);
-                            let v = dsq as f32
-                                * (1.0
-                                    - 0.5
-                                        * (3.1415
-                                            * (self.frame - (bomb.start + bomb.duration - 20))
-                                                as f32
-                                            / 20.0)
-                                            .sin());
-                            sum += (255 - 16 * v as i32).clamp(0, 255);
-                        }
-                    }
-                    sum.clamp(0, 255) as u8
-                };
+        let theme = &state.theme;
+        let shapes = state.storage.display.shapes;
+
+        let view = View::new(state);
+        let (HFOV, VFOV, TILE_WIDTH) = (view.hfov, view.vfov, view.tile);
+
+        let cam = self.drawn_cam(state);
+
+        let lights = LightMap::build(
+            &self.map,
+            &Light::collect(&self.map, theme, self.frame, (cam.x, cam.y), &view),
+            [
+                max(cam.x - HFOV, 0.0),
+                max(cam.y - VFOV, 0.0),
+                min(cam.x + HFOV, (self.map.w - 1) as f32),
+                min(cam.y + VFOV, (self.map.h - 1) as f32),
+            ],
+        );
 
-                if alpha <= 8 {
+        // remembered tiles fade in together with the player light
+        let memory = MEMORY * ((self.frame - 30) as f32 / 30.0).clamp(0.0, 1.0);
+
+        for x in max(cam.x - HFOV, 0.0)..=min(cam.x + HFOV, (self.map.w - 1) as f32) {
+            for y in max(cam.y - VFOV, 0.0)..=min(cam.y + VFOV, (self.map.h - 1) as f32) {
+                let (vx, vy) = (cam.x - x as f32, cam.y - y as f32);
+
+                // tiles out of sight are drawn from memory, as long as they were ever lit
+                let visible = self.fog.visible(x, y);
+                let explored = self.fog.explored(x, y);
+                if !visible && !explored {
                     continue;
                 }
 
+                let light = if visible { lights.at(x, y) } else { [0.0; 3] };
+                let alpha = (light[0].max(light[1]).max(light[2]) * 255.0) as u8;
+                let light = light.map(|c| if explored { c.max(memory) } else { c });
+
                 match self.map.tiles[(x, y)] {
-                    Tile::None => {
-                        draw.rect()
-                            .rgba8(48, 48, 48, alpha)
-                            .x_y((-vx * TILE_WIDTH).round(), (vy * TILE_WIDTH).round())
-                            .w_h(TILE_WIDTH, TILE_WIDTH);
-                    }
-                    Tile::Wall(_) => {
-                        draw.rect()
-                            .rgba8(255, 165, 0, alpha)
-                            .x_y((-vx * TILE_WIDTH).round(), (vy * TILE_WIDTH).round())
-                            .w_h(TILE_WIDTH, TILE_WIDTH);
-                    }
-                    Tile::MovableWall(_) => {
+                    ref tile @ (Tile::Wall(_) | Tile::MovableWall(_)) => {
+                        let (px, py) = ((-vx * TILE_WIDTH).round(), (vy * TILE_WIDTH).round());
+                        let movable = matches!(tile, Tile::MovableWall(_));
+                        let wear = tile.wear();
+                        let c = if movable {
+                            theme.movable_wall
+                        } else {
+                            theme.wall
+                        }
+                        .shade(1.0 - wear * 0.45);
+
                         draw.rect()
-                            .rgba8(200, 150, 100, alpha)
-                            .x_y((-vx * TILE_WIDTH).round(), (vy * TILE_WIDTH).round())
+                            .color(lit(c, light))
+                            .x_y(px, py)
                             .w_h(TILE_WIDTH, TILE_WIDTH);
-                    }
-                    Tile::Chest(ref chest) => {
-                        let c = match chest.ty {
-                            ChestType::Ammo => ORANGE,
-                            ChestType::Bomb => RED,
-                            ChestType::Turret => BLUE,
-                            ChestType::Emp => PURPLE,
-                            ChestType::Food => TURQUOISE,
-                            ChestType::Rainbow => [ORANGE, RED, BLUE, PURPLE, TURQUOISE]
-                                [((self.frame / 30) % 5) as usize],
-                        };
 
-                        let (r, g, b) = (c.red, c.green, c.blue);
+                        // inner frame tells them apart from fixed walls
+                        if movable && shapes {
+                            draw.rect()
+                                .no_fill()
+                                .stroke_weight((TILE_WIDTH * 0.08).max(2.0).round())
+                                .stroke(lit(c.shade(0.5), light))
+                                .x_y(px, py)
+                                .w_h((TILE_WIDTH * 0.6).round(), (TILE_WIDTH * 0.6).round());
+                        }
+
+                        // a crack for every third of its hp that's gone
+                        for k in 0..(wear * 3.0) as i32 {
+                            let side = if k % 2 == 0 { 1.0 } else { -1.0 };
+                            draw.rect()
+                                .color(lit(c.shade(0.35), light))
+                                .x_y((px - side * TILE_WIDTH * 0.15).round(), py)
+                                .w_h((TILE_WIDTH * 0.06).max(2.0).round(), TILE_WIDTH * 0.7)
+                                .rotate(side * 0.6);
+                        }
+                    }
+                    Tile::Chest(ref chest) if visible => {
+                        let c = chest.ty.color(theme, self.frame);
 
                         draw.rect()
-                            .rgba8(48, 48, 48, alpha)
+                            .color(lit(theme.floor, light))
                             .x_y((-vx * TILE_WIDTH).round(), (vy * TILE_WIDTH).round())
                             .w_h(TILE_WIDTH, TILE_WIDTH);
 
@@ -109,32 +135,155 @@ impl Playing {
 
                         draw.rect()
                             .stroke_weight(4.0)
-                            .stroke(Rgba8::new(r, g, b, alpha))
-                            .rgba8(r / 2, g / 2, b / 2, alpha)
+                            .stroke(lit(c, light))
+                            .color(lit(c.shade(0.5), light))
                             .x_y((-vx * TILE_WIDTH).round(), (vy * TILE_WIDTH).round())
                             .w_h((TILE_WIDTH * 0.5).round(), (TILE_WIDTH * 0.5).round());
+
+                        if shapes {
+                            draw.pixtext(
+                                chest.ty.letter(),
+                                [(-vx * TILE_WIDTH).round(), (vy * TILE_WIDTH).round()],
+                                19 * state.scale() as u32,
+                                (0, 0),
+                                state.font.clone(),
+                            )
+                            .color(lit(theme.text, light));
+                        }
+                    }
+                    Tile::Door(ref door) => {
+                        let (px, py) = ((-vx * TILE_WIDTH).round(), (vy * TILE_WIDTH).round());
+                        draw.rect()
+                            .color(lit(theme.floor, light))
+                            .x_y(px, py)
+                            .w_h(TILE_WIDTH, TILE_WIDTH);
+
+                        // darker the more it's been beaten on, open ones fold against the side
+                        let c = theme
+                            .door
+                            .shade(0.5 + 0.5 * door.hp as f32 / DOOR_HP as f32);
+                        if door.open {
+                            draw.rect()
+                                .color(lit(c, light))
+                                .x_y((px - TILE_WIDTH * 0.4).round(), py)
+                                .w_h((TILE_WIDTH * 0.2).round(), TILE_WIDTH);
+                        } else {
+                            draw.rect()
+                                .color(lit(c, light))
+                                .x_y(px, py)
+                                .w_h(TILE_WIDTH, TILE_WIDTH);
+
+                            if shapes {
+                                draw.rect()
+                                    .color(lit(c.shade(0.4), light))
+                                    .x_y((px + TILE_WIDTH * 0.25).round(), py)
+                                    .w_h((TILE_WIDTH * 0.15).round(), (TILE_WIDTH * 0.15).round());
+                            }
+                        }
+                    }
+                    Tile::Water => {
+                        let (px, py) = ((-vx * TILE_WIDTH).round(), (vy * TILE_WIDTH).round());
+                        draw.rect()
+                            .color(lit(theme.water, light))
+                            .x_y(px, py)
+                            .w_h(TILE_WIDTH, TILE_WIDTH);
+
+                        if shapes {
+                            draw.rect()
+                                .color(lit(theme.water.mix([255, 255, 255], 0.4), light))
+                                .x_y(px, py)
+                                .w_h(
+                                    (TILE_WIDTH * 0.6).round(),
+                                    (TILE_WIDTH * 0.08).max(2.0).round(),
+                                );
+                        }
+                    }
+                    Tile::Spikes => {
+                        let (px, py) = ((-vx * TILE_WIDTH).round(), (vy * TILE_WIDTH).round());
+                        draw.rect()
+                            .color(lit(theme.floor, light))
+                            .x_y(px, py)
+                            .w_h(TILE_WIDTH, TILE_WIDTH);
+
+                        for (dx, dy) in [(-1.0, -1.0), (-1.0, 1.0), (1.0, -1.0), (1.0, 1.0)] {
+                            draw.rect()
+                                .color(lit(theme.spikes, light))
+                                .x_y(
+                                    (px + dx * TILE_WIDTH * 0.25).round(),
+                                    (py + dy * TILE_WIDTH * 0.25).round(),
+                                )
+                                .w_h((TILE_WIDTH * 0.15).round(), (TILE_WIDTH * 0.15).round());
+                        }
+                    }
+                    Tile::Barricade(ref barricade) => {
+                        let (px, py) = ((-vx * TILE_WIDTH).round(), (vy * TILE_WIDTH).round());
+                        draw.rect()
+                            .color(lit(theme.floor, light))
+                            .x_y(px, py)
+                            .w_h(TILE_WIDTH, TILE_WIDTH);
+
+                        let c = theme
+                            .barricade
+                            .shade(0.5 + 0.5 * barricade.hp as f32 / BARRICADE_HP as f32);
+                        draw.rect()
+                            .color(lit(c, light))
+                            .stroke_weight((TILE_WIDTH * 0.08).max(2.0).round())
+                            .stroke(lit(c.shade(0.5), light))
+                            .x_y(px, py)
+                            .w_h((TILE_WIDTH * 0.8).round(), (TILE_WIDTH * 0.8).round());
+                    }
+                    Tile::None | Tile::Chest(_) => {
+                        draw.rect()
+                            .color(lit(theme.floor, light))
+                            .x_y((-vx * TILE_WIDTH).round(), (vy * TILE_WIDTH).round())
+                            .w_h(TILE_WIDTH, TILE_WIDTH);
                     }
-                    _ => {}
+                }
+
+                // enemies are only seen in line of sight and light
+                if !visible || alpha <= 8 {
+                    continue;
                 }
                 match self.map.enemies[(x, y)] {
                     Some(ref enemy) => {
-                        let (r, g, b) = match enemy.ty {
-                            EnemyType::Zombie => (
-                                0,
-                                100 + (155.0 * enemy.hp as f32 / enemy.mhp as f32) as u8,
-                                0,
-                            ),
-                            EnemyType::ZombieBoss(_) => (
-                                100,
-                                0,
-                                (100.0 + (self.frame as f32 / 30.0).sin() * 25.0) as u8,
+                        let c = match enemy.ty {
+                            EnemyType::Zombie => theme.zombie.shade(
+                                (100.0 + 155.0 * enemy.hp as f32 / enemy.mhp as f32) / 255.0,
                             ),
+                            EnemyType::ZombieBoss(_) => theme
+                                .boss
+                                .shade(0.8 + (self.frame as f32 / 30.0).sin() * 0.2),
                         };
 
                         draw.rect()
-                            .rgba8(r, g, b, alpha)
+                            .color(lit(c, light))
                             .x_y((-vx * TILE_WIDTH).round(), (vy * TILE_WIDTH).round())
                             .w_h(TILE_WIDTH, TILE_WIDTH);
+
+                        if shapes && matches!(enemy.ty, EnemyType::ZombieBoss(_)) {
+                            draw.rect()
+                                .no_fill()
+                                .stroke_weight((TILE_WIDTH * 0.1).max(2.0).round())
+                                .stroke(lit(theme.text, light))
+                                .x_y((-vx * TILE_WIDTH).round(), (vy * TILE_WIDTH).round())
+                                .w_h((TILE_WIDTH * 0.7).round(), (TILE_WIDTH * 0.7).round());
+                        }
+
+                        // health bar along the top edge once damaged
+                        if enemy.hp < enemy.mhp {
+                            let p = (enemy.hp as f32 / enemy.mhp as f32).max(0.0);
+                            let (w, h) = (TILE_WIDTH * 0.8, (TILE_WIDTH * 0.12).max(3.0));
+                            let (x, y) = (-vx * TILE_WIDTH, vy * TILE_WIDTH + TILE_WIDTH * 0.4);
+
+                            draw.rect()
+                                .x_y(x.round(), y.round())
+                                .w_h(w.round(), h.round())
+                                .color(theme.bar.rgba(255));
+                            draw.rect()
+                                .x_y((x - w * (0.5 - p * 0.5)).round(), y.round())
+                                .w_h((w * p).round(), h.round())
+                                .color(theme.health.rgba(255));
+                        }
                     }
                     None => {}
                 }
@@ -177,33 +326,40 @@ impl Playing {
                                 (bomb.y + ((bomb.x - x).abs() - radius).abs()) as f32,
                             )
                         {
-                            let (vx, vy) = (self.cam.x - x as f32, self.cam.y - y as f32);
+                            let (vx, vy) = (cam.x - x as f32, cam.y - y as f32);
 
                             draw.rect()
-                                .rgba8(255, 0, 0, 255)
+                                .color(theme.blast.rgba(255))
                                 .x_y((-vx * TILE_WIDTH).round(), (vy * TILE_WIDTH).round())
                                 .w_h(TILE_WIDTH, TILE_WIDTH);
                         }
                     }
                 }
                 n @ 20.. => {
-                    let (vx, vy) = (self.cam.x - x as f32, self.cam.y - y as f32);
+                    let (vx, vy) = (cam.x - x as f32, cam.y - y as f32);
 
                     let elapsed = self.frame - bomb.start;
 
                     let v = (3.1415 * elapsed.pow(2) as f32 / 3600.0).cos().abs();
 
                     draw.rect()
-                        .rgba8(255, (v * 255.0) as u8, (v * 255.0) as u8, 255)
+                        .color(theme.bomb.mix([255, 255, 255], v).rgba(255))
                         .x_y((-vx * TILE_WIDTH).round(), (vy * TILE_WIDTH).round())
                         .w_h(TILE_WIDTH, TILE_WIDTH);
+
+                    if shapes {
+                        draw.ellipse()
+                            .color(theme.background.rgba(255))
+                            .x_y((-vx * TILE_WIDTH).round(), (vy * TILE_WIDTH).round())
+                            .w_h((TILE_WIDTH * 0.4).round(), (TILE_WIDTH * 0.4).round());
+                    }
                 }
                 _ => {}
             }
         }
 
         for turret in &self.map.turrets {
-            let (vx, vy) = (self.cam.x - turret.x as f32, self.cam.y - turret.y as f32);
+            let (vx, vy) = (cam.x - turret.x as f32, cam.y - turret.y as f32);
 
             let (cx, cy) = (-(vx * TILE_WIDTH).round(), (vy * TILE_WIDTH).round());
 
@@ -213,15 +369,32 @@ impl Playing {
 
             // TODO: optimize so that it draws one rect instead of 2 with alpha
             draw.rect()
-                .rgba8(0, 0, 255, 255)
+                .color(theme.turret.rgba(255))
                 .x_y(cx, cy)
                 .w_h(TILE_WIDTH, TILE_WIDTH);
 
             draw.rect()
-                .rgba8(255, 0, 0, ((p - 0.5) * 255.0 * 2.0).clamp(0.0, 255.0) as u8)
+                .color(
+                    theme
+                        .turret_charged
+                        .rgba(((p - 0.5) * 255.0 * 2.0).clamp(0.0, 255.0) as u8),
+                )
                 .x_y(cx, cy)
                 .w_h(TILE_WIDTH, TILE_WIDTH);
 
+            // a plus sign so turrets don't look like bombs
+            if shapes {
+                let (long, thin) = ((TILE_WIDTH * 0.6).round(), (TILE_WIDTH * 0.15).round());
+                draw.rect()
+                    .color(theme.background.rgba(255))
+                    .x_y(cx, cy)
+                    .w_h(long, thin);
+                draw.rect()
+                    .color(theme.background.rgba(255))
+                    .x_y(cx, cy)
+                    .w_h(thin, long);
+            }
+
             // let pp = (3.0 * p * 255.0).clamp(0.0, 255.0) as u8;
             // draw.rect()
             // .rgba8(255, 0, 0, pp)
@@ -290,12 +463,12 @@ impl Playing {
         }
 
         for bullet in &self.map.bullets {
-            let (vx, vy) = (self.cam.x - bullet.x as f32, self.cam.y - bullet.y as f32);
+            let (vx, vy) = (cam.x - bullet.x as f32, cam.y - bullet.y as f32);
 
-            let g = std::cmp::min((self.frame - bullet.start) * 2, 255) as u8;
+            let age = (self.frame - bullet.start) as f32 * 2.0 / 255.0;
 
             draw.rect()
-                .rgba8(255, g, 0, 255)
+                .color(theme.bullet.mix(theme.bullet_old, age).rgba(255))
                 .x_y(-(vx * TILE_WIDTH).round(), (vy * TILE_WIDTH).round())
                 .w_h(TILE_WIDTH, TILE_WIDTH);
         }
@@ -316,16 +489,13 @@ impl Playing {
                     emp.x as f32 + emp.radius as f32 * angle.cos(),
                     emp.y as f32 + emp.radius as f32 * angle.sin(),
                 );
-                let (vx, vy) = (self.cam.x - x.round(), self.cam.y - y.round());
+                let (vx, vy) = (cam.x - x.round(), cam.y - y.round());
 
                 draw.rect()
-                    .rgba8(
-                        0x89,
-                        0xD1,
-                        0xFE,
+                    .color(theme.emp.rgba(
                         ((8.0 * 3.1415 * k as f32 / (7 * emp.radius) as f32).sin() * 255.0 * q)
                             as u8,
-                    )
+                    ))
                     .x_y((-vx * TILE_WIDTH).round(), (vy * TILE_WIDTH).round())
                     .w_h(TILE_WIDTH, TILE_WIDTH);
             }
@@ -348,13 +518,13 @@ impl Playing {
             // }
         }
 
-        let (vx, vy) = (
-            self.cam.x - self.player.x as f32,
-            self.cam.y - self.player.y as f32,
-        );
+        self.effects.render(draw, &self.fog, &cam, TILE_WIDTH);
+        self.popups.render(draw, state, &self.fog, &cam, TILE_WIDTH);
+
+        let (vx, vy) = (cam.x - self.player.x as f32, cam.y - self.player.y as f32);
 
         draw.rect()
-            .rgba8(255, 255, 255, 255)
+            .color(theme.player.rgba(255))
             .x_y(-(vx * TILE_WIDTH).round(), (vy * TILE_WIDTH).round())
             .w_h(TILE_WIDTH, TILE_WIDTH);
 
@@ -373,7 +543,7 @@ impl Playing {
                 };
 
                 draw.rect()
-                    .rgba8(0, 255, 0, 255)
+                    .color(theme.marker.rgba(255))
                     .x_y(
                         (-vx * TILE_WIDTH
                             + ((TILE_WIDTH - 8.0) * 0.5 * angle.cos() * len)
@@ -398,17 +568,41 @@ impl Playing {
             square(angle + 0.2);
         }
 
+        if state.storage.display.mouse_aim && !self.player.dead {
+            let m = app.mouse.position();
+            let tx = (cam.x + m.x / TILE_WIDTH).round();
+            let ty = (cam.y - m.y / TILE_WIDTH).round();
+            draw.rect()
+                .no_fill()
+                .stroke(theme.accent.rgba(200))
+                .stroke_weight((TILE_WIDTH * 0.06).max(2.0).round())
+                .x_y(
+                    ((tx - cam.x) * TILE_WIDTH).round(),
+                    ((cam.y - ty) * TILE_WIDTH).round(),
+                )
+                .w_h(TILE_WIDTH, TILE_WIDTH);
+        }
+
         self.draw_stats(app, draw, state);
 
+        self.minimap.render(
+            draw,
+            state,
+            &self.map,
+            &self.fog,
+            (self.player.x, self.player.y),
+            self.frame,
+        );
+
         if self.player.dead {
             draw.pixtext(
-                "You are DEAD",
+                state.lang.get("hud.dead"),
                 [0.0, state.h * 0.5 - 182.0],
                 19 * 4,
                 (0, 0),
                 state.font.clone(),
             )
-            .rgba8(128, 0, 0, 255);
+            .color(theme.danger.rgba(255));
 
             // draw.pixtext(
             //     "press SPACE to continue",
@@ -420,6 +614,18 @@ impl Playing {
             // .rgba8(255, 255, 255, 255);
         } else if let Some(ref upgrading) = self.player.upgrading {
             upgrading.render(app, draw, state);
+        } else if self.paused {
+            draw.rect()
+                .w_h(state.w, state.h)
+                .color(theme.background.rgba(160));
+            draw.pixtext(
+                state.lang.get("hud.paused"),
+                [0.0, 0.0],
+                19 * 4,
+                (0, 0),
+                state.font.clone(),
+            )
+            .color(theme.text.rgba(255));
         }
     }
     pub fn update(&mut self, app: &App, state: &mut State) -> Option<Box<Screen>> {
@@ -451,9 +657,17 @@ impl Playing {
             _ => {}
         }
 
-        if state.keys[state.storage.hotkeys[Hotkey::Shoot as usize] as usize] {
+        if self.paused {
+            return None;
+        }
+
+        let mouse_aim = state.storage.display.mouse_aim;
+        let dir = self.direction();
+        let aim = if mouse_aim { self.aim(app, state) } else { dir };
+
+        if state.held(Action::Shoot) || (mouse_aim && app.mouse.buttons.left().is_down()) {
             check_shot(
-                &self.movement,
+                aim,
                 &mut self.player,
                 &mut self.map.bullets,
                 &state,
@@ -461,12 +675,12 @@ impl Playing {
             );
         }
 
-        let can_move = !state.keys[state.storage.hotkeys[Hotkey::Shoot as usize] as usize]
-            && !state.keys[state.storage.hotkeys[Hotkey::Turret as usize] as usize];
+        // with the cursor aiming the direction keys are free to keep moving
+        let can_move = mouse_aim || (!state.held(Action::Shoot) && !state.held(Action::Turret));
         if can_move {
             make_moves(
                 &mut self.player,
-                &self.movement,
+                dir,
                 &mut self.map,
                 &state,
                 self.frame,
@@ -491,15 +705,47 @@ impl Playing {
         }
         if self.player[Score] >= 200 + 400 * self.map.boss.nth && self.map.boss.pos.is_none() {
             let hp = 3 + self.map.boss.nth as i8;
-            let (x, y) = spawn_boss(&mut self.map, &self.player, state, self.frame, hp);
-            self.map.boss.nth += 1;
-            self.map.boss.pos = Some(Position { x, y });
-            self.map.boss.resp = Some(self.frame);
+            if let Some((x, y)) = spawn_boss(&mut self.map, &self.player, state, self.frame, hp) {
+                self.map.boss.nth += 1;
+                self.map.boss.pos = Some(Position { x, y });
+                self.map.boss.resp = Some(self.frame);
+            }
         }
-        update_enemies(&mut self.map, &mut self.player, state, self.frame);
+        self.flow.update(&self.map, (self.player.x, self.player.y));
+        update_enemies(
+            &mut self.map,
+            &self.flow,
+            &mut self.player,
+            state,
+            self.frame,
+        );
 
         check_bullets(&mut self.map, &mut self.player, state, self.frame);
 
+        let (px, py) = (self.player.x, self.player.y);
+        self.map.stream((px, py));
+        // what the player light shows this tick, only lit tiles are remembered
+        let lights = LightMap::build(
+            &self.map,
+            &Light::collect(
+                &self.map,
+                &state.theme,
+                self.frame,
+                (self.cam.x, self.cam.y),
+                &View::new(state),
+            ),
+            [px - SIGHT, py - SIGHT, px + SIGHT, py + SIGHT],
+        );
+        self.fog.update(&self.map, (px, py), self.frame, &lights);
+        self.minimap.update(
+            app,
+            &state.theme,
+            &self.map,
+            &self.fog,
+            (px, py),
+            self.frame,
+        );
+
         // remove chest if too old :)
         if let Some(&(x, y)) = self.map.chests.get(0) {
             if let Tile::Chest(ref chest) = self.map.tiles[(x, y)] {
@@ -522,23 +768,17 @@ impl Playing {
             i += 1;
         }
 
-        match self.ambient {
-            Some(ref s) if s.len() == 0 => {
-                self.ambient = Some(state.play_get(Sound::Ambient, 0.2));
-            }
-            _ => {}
+        if let Some(ref mut music) = self.music {
+            music.update(state, &Intensity::measure(&self.map, &self.player));
         }
 
         // update enemies max hp
         if score / 1000 != self.player[Score] / 1000 {
             let mut mhp = 1 + (self.player[Score] + 1) / 1000;
 
-            for x in 1..self.map.w - 1 {
-                for y in 1..self.map.h - 1 {
-                    if let Some(ref mut enemy) = self.map.enemies[(x, y)] {
-                        enemy.mhp = mhp as i8;
-                    }
-                }
+            // only chunks that were ever written can hold zombies, the endless map is mostly empty
+            for enemy in self.map.enemies.values_mut().flatten() {
+                enemy.mhp = mhp as i8;
             }
         }
 
@@ -553,8 +793,14 @@ impl Playing {
             self.player.dead = true;
         }
 
-        self.cam.x += (px as f32 - self.cam.x) * 0.05;
-        self.cam.y += (py as f32 - self.cam.y) * 0.05;
+        self.cam.follow(px as f32, py as f32);
+
+        for event in self.map.events.drain(..) {
+            self.effects.handle(&event, (px, py), state);
+            self.popups.handle(&event, state);
+        }
+        self.effects.update();
+        self.popups.update();
 
         self.frame += 1;
         self.player[Food] -= 85;
@@ -647,33 +893,40 @@ impl Playing {
 
         None
     }
-    pub fn pressed(&mut self, app: &App, state: &mut State, key: Key) {
-
-        if key == Key::Escape {
+    pub fn pressed(&mut self, app: &App, state: &mut State, input: Input, action: Option<Action>) {
+        if input == Input::Key(Key::Escape) {
+            if let Some(music) = self.music.take() {
+                music.fade(state, 1000);
+            }
             self.next = Some(Box::new(Screen::Menu(Menu::new(state))));
             return;
         }
 
         match (self.player.paused, &mut self.player.upgrading) {
             (_, Some(ref mut upgrading)) => {
-                upgrading.pressed(app, state, key);
+                upgrading.pressed(app, state, input);
                 return;
             }
             (true, _) => return,
             _ => {}
         }
 
-        if (key as u32 == state.storage.hotkeys[Hotkey::Up as usize]
-            || key as u32 == state.storage.hotkeys[Hotkey::Right as usize]
-            || key as u32 == state.storage.hotkeys[Hotkey::Down as usize]
-            || key as u32 == state.storage.hotkeys[Hotkey::Left as usize])
-            && !self.movement.contains(&key)
-        {
-            self.movement.push(key);
+        if action == Some(Action::Pause) {
+            self.paused = !self.paused;
+            return;
+        }
+        if self.paused {
+            return;
+        }
+
+        if let Some(dir) = action.and_then(Action::direction) {
+            if !self.movement.iter().any(|&(k, _)| k == input) {
+                self.movement.push((input, dir));
+            }
 
-            if state.keys[state.storage.hotkeys[Hotkey::Turret as usize] as usize] {
+            if self.placing {
                 check_turret(
-                    &self.movement,
+                    self.direction(),
                     &mut self.player,
                     &mut self.map,
                     state,
@@ -684,51 +937,123 @@ impl Playing {
             return;
         }
 
-        match key {
-            _ if key as u32 == state.storage.hotkeys[Hotkey::Run as usize] => {
-                self.player.running = true
-            }
-            _ if key as u32 == state.storage.hotkeys[Hotkey::Bomb as usize] => {
-                check_bomb(&mut self.player, &mut self.map, state, self.frame)
-            }
-            _ if key as u32 == state.storage.hotkeys[Hotkey::Emp as usize] => {
-                check_emp(&mut self.player, &mut self.map, state, self.frame)
+        match action {
+            Some(Action::Run) => self.player.running = true,
+            Some(Action::Turret) if state.storage.display.mouse_aim => check_turret(
+                self.aim(app, state),
+                &mut self.player,
+                &mut self.map,
+                state,
+                self.frame,
+            ),
+            Some(Action::Turret) => self.placing = true,
+            Some(Action::Bomb) => check_bomb(&mut self.player, &mut self.map, state, self.frame),
+            Some(Action::Emp) => check_emp(&mut self.player, &mut self.map, state, self.frame),
+            Some(Action::Use) => {
+                let dir = if state.storage.display.mouse_aim {
+                    self.aim(app, state)
+                } else {
+                    self.direction()
+                };
+                check_use(dir, &mut self.player, &mut self.map, state);
             }
-            _ => {}
+            _ => match input {
+                Input::Key(Key::Minus | Key::NumpadSubtract) => self.zoom(state, -1),
+                Input::Key(Key::Equals | Key::NumpadAdd) => self.zoom(state, 1),
+                Input::Key(Key::Tab) | Input::Pad(Pad::Select) => {
+                    self.minimap.full = !self.minimap.full
+                }
+                _ => {}
+            },
         }
     }
-    pub fn released(&mut self, app: &App, state: &mut State, key: Key) {
-        if let Some((idx, _)) = self.movement.iter().enumerate().find(|&(_i, &k)| k == key) {
-            self.movement.remove(idx);
+    fn zoom(&mut self, state: &mut State, d: i32) {
+        state.storage.display.zoom = zoom_step(state.storage.display.zoom, d);
+        state.storage.save();
+    }
+    // the most recently pressed movement direction still held
+    fn direction(&self) -> Option<usize> {
+        self.movement.last().map(|&(_, dir)| dir)
+    }
+    // the camera as it's drawn this frame, between ticks and shaken
+    fn drawn_cam(&self, state: &State) -> Camera {
+        let mut cam = self.cam.lerp(state.alpha);
+        cam.x += self.effects.shake.0;
+        cam.y += self.effects.shake.1;
+        cam
+    }
+    // from the player towards the highlighted tile under the cursor, snapped to the closest axis
+    fn aim(&self, app: &App, state: &State) -> Option<usize> {
+        let (m, tile) = (app.mouse.position(), View::new(state).tile);
+        let cam = self.drawn_cam(state);
+        // the highlighted tile, relative to the player
+        let dx = (cam.x + m.x / tile).round() - self.player.x as f32;
+        let dy = (cam.y - m.y / tile).round() - self.player.y as f32;
+        if dx == 0.0 && dy == 0.0 {
+            return None;
         }
 
-        if self.player.paused {
+        let d = if dx.abs() > dy.abs() {
+            (dx.signum() as i32, 0)
+        } else {
+            (0, dy.signum() as i32)
+        };
+        DIRECTIONS.iter().position(|&dir| dir == d)
+    }
+    pub fn mpressed(&mut self, app: &App, state: &mut State, mb: MouseButton) {
+        if let Some(ref mut upgrading) = self.player.upgrading {
+            upgrading.mpressed(app, state, mb);
+            return;
+        }
+        if self.player.paused || self.paused || !state.storage.display.mouse_aim {
             return;
         }
 
-        match key {
-            _ if key as u32 == state.storage.hotkeys[Hotkey::Run as usize] => {
-                self.player.running = false
-            }
-            _ if key as u32 == state.storage.hotkeys[Hotkey::Turret as usize] => check_turret(
-                &self.movement,
+        // left is polled every tick for shooting, right places a turret
+        if mb == MouseButton::Right {
+            check_turret(
+                self.aim(app, state),
                 &mut self.player,
                 &mut self.map,
                 state,
                 self.frame,
-            ),
-            _ => {}
+            );
+        }
+    }
+    pub fn released(&mut self, app: &App, state: &mut State, input: Input) {
+        self.movement.retain(|&(k, _)| k != input);
+
+        if self.player.paused || self.paused {
+            return;
+        }
+
+        // the release may come from either binding, so check what is still held
+        if self.player.running && !state.held(Action::Run) {
+            self.player.running = false;
+        }
+        if self.placing && !state.held(Action::Turret) {
+            self.placing = false;
+            check_turret(
+                self.direction(),
+                &mut self.player,
+                &mut self.map,
+                state,
+                self.frame,
+            );
         }
     }
 
     pub fn draw_stats(&self, app: &App, draw: &Draw, state: &State) {
-        let SIZE: u32 = 38;
-        let BAR_WIDTH: f32 = 200.0;
-        let BAR_HEIGHT: f32 = 24.0;
-        let OFFSET: f32 = 12.0;
+        let theme = &state.theme;
+        let s = state.scale();
+        let SIZE: u32 = 38 * s as u32;
+        let OFFSET: f32 = 12.0 * s;
+        // four bars share the bottom edge, narrow windows shrink them
+        let BAR_WIDTH: f32 = (200.0 * s).min(((state.w - OFFSET * 6.0) / 4.0).floor());
+        let BAR_HEIGHT: f32 = 24.0 * s;
 
         let bar = |draw: &Draw, [x, y, w, h]: [f32; 4], p: f32, color: Rgba8| {
-            draw.rect().x_y(x, y).w_h(w, h).rgba8(39, 39, 39, 255);
+            draw.rect().x_y(x, y).w_h(w, h).color(theme.bar.rgba(255));
 
             draw.rect()
                 .x_y((x - w * (0.5 - p * 0.5)).round(), y)
@@ -742,15 +1067,15 @@ impl Playing {
 
                 draw.pixtext(
                     bt,
-                    [x, y + h * 0.5 + 24.0],
-                    19 * 2,
+                    [x, y + h * 0.5 + 24.0 * s],
+                    SIZE,
                     (0, 0),
                     state.font.clone(),
                 );
                 draw.pixtext(
                     ut,
-                    [x, y + h * 0.5 + 24.0 + 36.0],
-                    19,
+                    [x, y + h * 0.5 + (24.0 + 36.0) * s],
+                    SIZE / 2,
                     (0, 0),
                     state.font.clone(),
                 );
@@ -765,9 +1090,9 @@ impl Playing {
                 BAR_HEIGHT,
             ],
             self.player[Ammo] as f32 / self.player[MaxAmmo] as f32,
-            Rgba8::new(255, 165, 0, 255),
+            theme.ammo.rgba(255),
             &*format!("{}/{}", self.player[Ammo], self.player[MaxAmmo]),
-            "AMMO",
+            state.lang.get("hud.ammo"),
         );
 
         sbar(
@@ -779,9 +1104,9 @@ impl Playing {
                 BAR_HEIGHT,
             ],
             self.player[Bomb] as f32 / self.player[MaxBomb] as f32,
-            Rgba8::new(255, 0, 0, 255),
+            theme.bombs.rgba(255),
             &*format!("{}/{}", self.player[Bomb], self.player[MaxBomb]),
-            "BOMB",
+            state.lang.get("hud.bomb"),
         );
 
         sbar(
@@ -793,9 +1118,9 @@ impl Playing {
                 BAR_HEIGHT,
             ],
             self.player[Turret] as f32 / self.player[MaxTurret] as f32,
-            Rgba8::new(0, 0, 255, 255),
+            theme.turrets.rgba(255),
             &*format!("{}/{}", self.player[Turret], self.player[MaxTurret]),
-            "TURRET",
+            state.lang.get("hud.turret"),
         );
 
         sbar(
@@ -807,9 +1132,9 @@ impl Playing {
                 BAR_HEIGHT,
             ],
             self.player[Emp] as f32 / self.player[MaxEmp] as f32,
-            Rgba8::new(128, 0, 128, 255),
+            theme.emps.rgba(255),
             &*format!("{}/{}", self.player[Emp], self.player[MaxEmp]),
-            "EMP",
+            state.lang.get("hud.emp"),
         );
 
         bar(
@@ -821,45 +1146,45 @@ impl Playing {
                 BAR_HEIGHT,
             ],
             self.player[Food] as f32 / self.player[MaxFood] as f32,
-            Rgba8::new(64, 224, 208, 255),
+            theme.food.rgba(255),
         );
 
         draw.pixtext(
-            "FOOD",
+            state.lang.get("hud.food"),
             [
                 state.w * 0.5 - BAR_WIDTH * 1.0 - OFFSET * 1.5,
-                state.h * 0.5 - BAR_HEIGHT * 1.0 - OFFSET - 24.0,
+                state.h * 0.5 - BAR_HEIGHT * 1.0 - OFFSET - 24.0 * s,
             ],
-            19,
+            SIZE / 2,
             (0, 0),
             state.font.clone(),
         )
-        .rgba8(255, 255, 255, 255);
+        .color(theme.text.rgba(255));
 
         {
             draw.pixtext(
                 format!("{}", self.player[Score]),
                 [
                     -state.w * 0.5 + BAR_WIDTH * 0.5 + OFFSET,
-                    state.h * 0.5 - (BAR_WIDTH * 0.5 + OFFSET) + 32.0,
+                    state.h * 0.5 - (BAR_WIDTH * 0.5 + OFFSET) + 32.0 * s,
                 ],
-                38,
+                SIZE,
                 (0, 0),
                 state.font.clone(),
             )
-            .rgba8(255, 255, 255, 255);
+            .color(theme.text.rgba(255));
 
             draw.pixtext(
-                "SCORE",
+                state.lang.get("hud.score"),
                 [
                     -state.w * 0.5 + BAR_WIDTH * 0.5 + OFFSET,
                     state.h * 0.5 - (BAR_WIDTH * 0.5 + OFFSET),
                 ],
-                19,
+                SIZE / 2,
                 (0, 0),
                 state.font.clone(),
             )
-            .rgba8(255, 255, 255, 255);
+            .color(theme.text.rgba(255));
         }
     }
 }
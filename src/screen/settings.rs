@@ -1,20 +1,69 @@
 use super::*;
 
 static DESC: &[&str] = &[
-    "Up", "Left", "Down", "Right", "Shoot", "Bomb", "Turret", "Emp", "Run",
+    "settings.up",
+    "settings.left",
+    "settings.down",
+    "settings.right",
+    "settings.shoot",
+    "settings.bomb",
+    "settings.turret",
+    "settings.emp",
+    "settings.use",
+    "settings.run",
+    "settings.pause",
+];
+
+static DISPLAY: &[&str] = &[
+    "settings.window",
+    "settings.size",
+    "settings.monitor",
+    "settings.vsync",
+    "settings.zoom",
+    "settings.particles",
+    "settings.shake",
+    "settings.theme",
+    "settings.shapes",
+    "settings.language",
+    "settings.mouse_aim",
+    "settings.map_size",
+    "settings.map_shape",
+];
+
+// rows never get closer than this, whatever doesn't fit goes on another page
+const MIN_STEP: f32 = 32.0;
+
+static MODES: &[WindowMode] = &[
+    WindowMode::Fullscreen,
+    WindowMode::Borderless,
+    WindowMode::Windowed,
 ];
 
 pub struct Settings {
+    frame: i32,
     n: usize,
+    // primary or secondary binding of the selected action
+    slot: usize,
     choosing: bool,
+    // held while choosing, becomes part of the binding
+    modifier: Option<Input>,
+    // the cursor only selects rows after it moves, so it doesn't fight the keyboard
+    mouse: Option<Vec2>,
+    // action that already had the binding and when it was refused
+    conflict: Option<(Action, i32)>,
     next: Option<Box<Screen>>,
 }
 
 impl Settings {
     pub fn new() -> Self {
         Self {
+            frame: 0,
             n: 0,
+            slot: 0,
             choosing: false,
+            modifier: None,
+            mouse: None,
+            conflict: None,
             next: None,
         }
     }
@@ -23,80 +72,333 @@ impl Settings {
 
         let m = app.mouse.position();
 
-        for k in 0..state.storage.hotkeys.len() {
-            let [r, g, b, a] = if k == self.n {
+        let (page, step) = self.layout(state);
+        let size = if step < 48.0 { 19 } else { 19 * 2 };
+
+        for k in page {
+            let color = if k == self.n {
                 if self.choosing {
-                    [255, 0, 0, 255]
+                    state.theme.accent
                 } else {
-                    [255, 255, 255, 255]
+                    state.theme.text
                 }
             } else {
-                [128, 128, 128, 255]
+                state.theme.dim
             };
 
-            draw.pixtext(
-                DESC[k],
-                [-128.0, state.h * 0.25 - 64.0 * k as f32],
-                19 * 2,
-                (0, 0),
-                state.font.clone(),
-            )
-            .rgba8(r, g, b, a);
+            let (desc, value) = self.row(app, state, k);
+            let y = self.y(state, step, k);
 
-            draw.pixtext(
-                format!("{:?}", KEYS[state.storage.hotkeys[k] as usize]),
-                [128.0, state.h * 0.25 - 64.0 * k as f32],
-                19 * 2,
-                (0, 0),
-                state.font.clone(),
-            )
-            .rgba8(r, g, b, a);
+            draw.pixtext(desc, [-128.0, y], size, (0, 0), state.font.clone())
+                .color(color.rgba(255));
+
+            draw.pixtext(value, [128.0, y], size, (0, 0), state.font.clone())
+                .color(color.rgba(255));
         }
 
         draw.pixtext(
-            "Settings",
+            state.lang.get("settings.title"),
             [0.0, state.h * 0.5 - 96.0],
             19 * 2,
             (0, 0),
             state.font.clone(),
         );
 
+        let (i, pages) = (self.page(state), self.pages(state));
+        let section = if pages[i].start < Action::ALL.len() {
+            "settings.controls"
+        } else {
+            "settings.display"
+        };
         draw.pixtext(
-            "press SPACE to select",
+            format!("< {} {}/{} >", state.lang.get(section), i + 1, pages.len()),
+            [0.0, state.h * 0.5 - 128.0],
+            19,
+            (0, 0),
+            state.font.clone(),
+        )
+        .color(state.theme.text.rgba(255));
+
+        if let Some((action, at)) = self.conflict {
+            if self.frame - at < 120 {
+                draw.pixtext(
+                    format!(
+                        "{} {}",
+                        state.lang.get("settings.conflict"),
+                        state.lang.get(DESC[action as usize])
+                    ),
+                    [0.0, -state.h * 0.5 + 80.0],
+                    19,
+                    (0, 0),
+                    state.font.clone(),
+                )
+                .color(state.theme.danger.rgba(255));
+            }
+        }
+
+        draw.pixtext(
+            state.lang.get("settings.select"),
             [0.0, -state.h * 0.5 + 32.0],
             19,
             (0, 0),
             state.font.clone(),
         )
-        .rgba8(255, 255, 255, 255);
+        .color(state.theme.text.rgba(255));
 
         draw.pixtext(
-            "press S to close settings",
+            state.lang.get("settings.close"),
             [-state.w * 0.5 + 156.0, -state.h * 0.5 + 32.0],
             19,
             (0, 0),
             state.font.clone(),
         )
-        .rgba8(255, 255, 255, 255);
+        .color(state.theme.text.rgba(255));
     }
     pub fn update(&mut self, app: &App, state: &mut State) -> Option<Box<Screen>> {
+        self.frame += 1;
+
+        let m = app.mouse.position();
+        if self.mouse.is_some_and(|last| last != m) && !self.choosing {
+            if let Some(k) = self.hovered(state, m) {
+                self.n = k;
+            }
+        }
+        self.mouse = Some(m);
+
         self.next.take()
     }
-    pub fn pressed(&mut self, app: &App, state: &mut State, k: Key) {
-        match k {
-            k @ _ if self.choosing => {
-                state.play(Sound::UiSwitch, 0.75);
-                state.storage.hotkeys[self.n] = k as u32;
-                self.choosing = false;
+    fn rows(&self, state: &State) -> usize {
+        Action::ALL.len() + DISPLAY.len()
+    }
+    // the rows on each page, as many as fit the window with the actions and the display
+    // settings on pages of their own
+    fn pages(&self, state: &State) -> Vec<Range<usize>> {
+        let fit = (((state.h - 320.0) / MIN_STEP) as usize).max(1);
+        let actions = Action::ALL.len();
+
+        let mut pages = vec![];
+        for section in [0..actions, actions..self.rows(state)] {
+            let mut start = section.start;
+            while start < section.end {
+                let end = (start + fit).min(section.end);
+                pages.push(start..end);
+                start = end;
+            }
+        }
+        pages
+    }
+    // the page the selected row is on
+    fn page(&self, state: &State) -> usize {
+        let pages = self.pages(state);
+        pages.iter().position(|p| p.contains(&self.n)).unwrap_or(0)
+    }
+    // rows of the current page and the distance between them
+    fn layout(&self, state: &State) -> (Range<usize>, f32) {
+        let page = self.pages(state).swap_remove(self.page(state));
+        let step = ((state.h - 320.0) / page.len() as f32).min(64.0).floor();
+        (page, step)
+    }
+    fn y(&self, state: &State, step: f32, k: usize) -> f32 {
+        let (page, _) = self.layout(state);
+        state.h * 0.5 - 160.0 - step * (k - page.start) as f32
+    }
+    fn hovered(&self, state: &State, m: Vec2) -> Option<usize> {
+        let (page, step) = self.layout(state);
+        page.into_iter()
+            .find(|&k| Rect::from_x_y_w_h(0.0, self.y(state, step, k), 640.0, step).contains(m))
+    }
+    // jumps to the first row of the page `d` pages over
+    fn turn(&mut self, state: &State, d: i32) {
+        let pages = self.pages(state);
+        let i = (self.page(state) as i32 + d).rem_euclid(pages.len() as i32);
+        state.play(Sound::UiSwitch, 0.75);
+        self.n = pages[i as usize].start;
+        self.slot = 0;
+    }
+    fn on_pages(state: &State, m: Vec2) -> bool {
+        m.x.abs() < 160.0 && (m.y - (state.h * 0.5 - 128.0)).abs() < 16.0
+    }
+    fn row<'a>(&self, app: &App, state: &'a State, k: usize) -> (&'a str, String) {
+        let lang = &state.lang;
+        let hotkeys = Action::ALL.len();
+        if k < hotkeys {
+            let slots =
+                state.storage.bindings.get(Action::ALL[k]).map(|binding| {
+                    binding.map_or_else(|| String::from("-"), |binding| binding.name())
+                });
+            let [primary, secondary] = match (k == self.n, self.slot) {
+                (true, 0) => [format!("[{}]", slots[0]), slots[1].clone()],
+                (true, _) => [slots[0].clone(), format!("[{}]", slots[1])],
+                _ => slots,
+            };
+            return (lang.get(DESC[k]), format!("{} / {}", primary, secondary));
+        }
+
+        let toggle =
+            |on: bool| String::from(lang.get(if on { "settings.on" } else { "settings.off" }));
+        let display = &state.storage.display;
+        let value = match k - hotkeys {
+            0 => String::from(lang.get(match display.mode {
+                WindowMode::Fullscreen => "mode.fullscreen",
+                WindowMode::Borderless => "mode.borderless",
+                WindowMode::Windowed => "mode.windowed",
+            })),
+            1 => format!("{}x{}", display.size.0, display.size.1),
+            2 => format!("{}/{}", display.monitor + 1, app.available_monitors().len()),
+            3 => toggle(display.vsync),
+            4 => format!("{}%", (display.zoom * 100.0).round()),
+            5 => toggle(display.particles),
+            6 => toggle(display.shake),
+            7 => {
+                let name = lang.get(match display.theme {
+                    Preset::Default => "theme.default",
+                    Preset::Deuteranopia => "theme.deuteranopia",
+                    Preset::Protanopia => "theme.protanopia",
+                    Preset::HighContrast => "theme.high_contrast",
+                    Preset::Custom => "theme.custom",
+                });
+                if display.theme == Preset::Custom && Theme::file().is_none() {
+                    format!("{} ({})", name, lang.get("settings.missing"))
+                } else {
+                    String::from(name)
+                }
+            }
+            8 => toggle(display.shapes),
+            9 => lang.name.clone(),
+            10 => toggle(display.mouse_aim),
+            11 => String::from(lang.get(state.storage.map_size.id())),
+            _ => String::from(lang.get(state.storage.map_shape.id())),
+        };
+        (lang.get(DISPLAY[k - hotkeys]), value)
+    }
+    // cycles the value of a display row, window changes are applied right away
+    fn change(&mut self, app: &App, state: &mut State, d: i32) {
+        let cycle = |idx: usize, len: usize| (idx as i32 + d).rem_euclid(len as i32) as usize;
+
+        let row = self.n - Action::ALL.len();
+        let language = &mut state.storage.language;
+        let display = &mut state.storage.display;
+        match row {
+            0 => {
+                let idx = MODES.iter().position(|&m| m == display.mode).unwrap_or(0);
+                display.mode = MODES[cycle(idx, MODES.len())];
             }
+            1 => {
+                let idx = RESOLUTIONS
+                    .iter()
+                    .position(|&r| r == display.size)
+                    .unwrap_or(0);
+                display.size = RESOLUTIONS[cycle(idx, RESOLUTIONS.len())];
+            }
+            2 => {
+                let monitors = app.available_monitors().len().max(1);
+                display.monitor = cycle(display.monitor, monitors);
+            }
+            3 => {
+                display.vsync = !display.vsync;
+            }
+            4 => {
+                display.zoom = zoom_step(display.zoom, d);
+            }
+            5 => {
+                display.particles = !display.particles;
+            }
+            6 => {
+                display.shake = !display.shake;
+            }
+            7 => {
+                let idx = Preset::ALL
+                    .iter()
+                    .position(|&p| p == display.theme)
+                    .unwrap_or(0);
+                display.theme = Preset::ALL[cycle(idx, Preset::ALL.len())];
+                state.theme = Theme::load(display.theme);
+            }
+            8 => {
+                display.shapes = !display.shapes;
+            }
+            9 => {
+                *language = language.cycle(d);
+                state.lang = Lang::load(*language);
+                state.font = state.lang.font().unwrap_or_else(font);
+            }
+            10 => {
+                display.mouse_aim = !display.mouse_aim;
+            }
+            11 => {
+                let size = &mut state.storage.map_size;
+                let idx = MapSize::ALL.iter().position(|s| s == size).unwrap_or(0);
+                *size = MapSize::ALL[cycle(idx, MapSize::ALL.len())];
+            }
+            _ => {
+                let shape = &mut state.storage.map_shape;
+                let idx = Shape::ALL.iter().position(|s| s == shape).unwrap_or(0);
+                *shape = Shape::ALL[cycle(idx, Shape::ALL.len())];
+            }
+        }
+
+        state.play(Sound::UiSwitch, 0.75);
+        // only the window rows touch the window, reapplying a fullscreen mode switches it again
+        if row <= 2 {
+            Context::apply_display(app, &state.storage.display);
+        }
+    }
+    pub fn pressed(&mut self, app: &App, state: &mut State, input: Input) {
+        if self.choosing {
+            match input {
+                Input::Key(Key::Escape) => {
+                    state.play(Sound::UiSwitch, 0.75);
+                    self.choosing = false;
+                    self.modifier = None;
+                }
+                // wait for the key it modifies, or its release
+                _ if input.is_modifier() && self.modifier.is_none() => {
+                    self.modifier = Some(input);
+                }
+                _ => {
+                    let modifier = self.modifier.take();
+                    self.bind(state, Binding::new(input, modifier));
+                }
+            }
+            return;
+        }
+
+        let actions = Action::ALL.len();
+        let Some(k) = input.key() else { return };
+        match k {
             Key::Up if self.n > 0 => {
                 state.play(Sound::UiSwitch, 0.75);
                 self.n -= 1;
             }
-            Key::Down if self.n < state.storage.hotkeys.len() - 1 => {
+            Key::Down if self.n < self.rows(state) - 1 => {
                 state.play(Sound::UiSwitch, 0.75);
                 self.n += 1;
             }
+            Key::Tab | Key::PageDown => {
+                self.turn(state, 1);
+            }
+            Key::PageUp => {
+                self.turn(state, -1);
+            }
+            Key::Space | Key::Right if self.n >= actions => {
+                self.change(app, state, 1);
+            }
+            Key::Left if self.n >= actions => {
+                self.change(app, state, -1);
+            }
+            Key::Left | Key::Right => {
+                state.play(Sound::UiSwitch, 0.75);
+                self.slot = 1 - self.slot;
+            }
+            // only the secondary can be cleared so every action stays reachable
+            Key::Back | Key::Delete if self.slot == 1 => {
+                state.play(Sound::UiSwitch, 0.75);
+                state
+                    .storage
+                    .bindings
+                    .bind(Action::ALL[self.n], 1, None)
+                    .ok();
+            }
             Key::Space => {
                 state.play(Sound::UiSwitch, 0.75);
                 self.choosing = true;
@@ -109,29 +411,57 @@ impl Settings {
             _ => {}
         }
     }
+    pub fn released(&mut self, app: &App, state: &mut State, input: Input) {
+        // a modifier let go before anything else was pressed is bound on its own
+        if self.choosing && self.modifier == Some(input) {
+            self.modifier = None;
+            self.bind(state, Binding::new(input, None));
+        }
+    }
+    fn bind(&mut self, state: &mut State, binding: Binding) {
+        state.play(Sound::UiSwitch, 0.75);
+        let action = Action::ALL[self.n];
+        if let Err(other) = state
+            .storage
+            .bindings
+            .bind(action, self.slot, Some(binding))
+        {
+            self.conflict = Some((other, self.frame));
+        }
+        self.choosing = false;
+    }
     pub fn mpressed(&mut self, app: &App, state: &mut State, mb: MouseButton) {
         let m = app.mouse.position();
 
         if m.x < -state.w * 0.5 + 164.0 && m.y < -state.h * 0.5 + 32.0 {
             state.storage.save();
             self.next = Some(Box::new(Screen::Menu(Menu::new(state))));
+            return;
         }
-    }
-}
 
-#[repr(u8)]
-pub enum Hotkey {
-    Up,
-    Left,
-    Down,
-    Right,
-
-    Shoot,
-    Bomb,
-    Turret,
-    Emp,
+        if self.choosing {
+            return;
+        }
+        if Self::on_pages(state, m) {
+            self.turn(state, if m.x < 0.0 { -1 } else { 1 });
+            return;
+        }
+        let Some(k) = self.hovered(state, m) else {
+            return;
+        };
+        self.n = k;
 
-    Run,
+        // the value column holds the primary binding left of the slash
+        if k < Action::ALL.len() {
+            state.play(Sound::UiSwitch, 0.75);
+            self.slot = if m.x < 128.0 { 0 } else { 1 };
+            self.choosing = true;
+        } else if mb == MouseButton::Right {
+            self.change(app, state, -1);
+        } else {
+            self.change(app, state, 1);
+        }
+    }
 }
 
 pub const KEYS: [Key; 163] = [
@@ -16,7 +16,7 @@ impl Tutorial {
     }
     pub fn render(&self, app: &App, draw: &Draw, state: &State) {
         draw.pixtext(
-            "Tutorial",
+            state.lang.get("tutorial.title"),
             [0.0, state.h * 0.5 - 96.0],
             19 * 2,
             (0, 0),
@@ -24,14 +24,14 @@ impl Tutorial {
         );
 
         draw.pixtext(
-            "CTRL + ARROW",
+            state.lang.get("tutorial.shoot.keys"),
             [-state.w * 0.30, 32.0],
             19 * 2,
             (0, 0),
             state.font.clone(),
         );
         draw.pixtext(
-            "SHOOT",
+            state.lang.get("tutorial.shoot"),
             [-state.w * 0.30, -32.0],
             19 * 2,
             (0, 0),
@@ -46,7 +46,7 @@ impl Tutorial {
             state.font.clone(),
         );
         draw.pixtext(
-            "BOMB",
+            state.lang.get("tutorial.bomb"),
             [-state.w * 0.15, -32.0],
             19 * 2,
             (0, 0),
@@ -54,17 +54,23 @@ impl Tutorial {
         );
 
         draw.pixtext("W", [0.0, 32.0], 19 * 2, (0, 0), state.font.clone());
-        draw.pixtext("RUN", [0.0, -32.0], 19 * 2, (0, 0), state.font.clone());
+        draw.pixtext(
+            state.lang.get("tutorial.run"),
+            [0.0, -32.0],
+            19 * 2,
+            (0, 0),
+            state.font.clone(),
+        );
 
         draw.pixtext(
-            "E + ARROW",
+            state.lang.get("tutorial.turret.keys"),
             [state.w * 0.15, 32.0],
             19 * 2,
             (0, 0),
             state.font.clone(),
         );
         draw.pixtext(
-            "TURRET",
+            state.lang.get("tutorial.turret"),
             [state.w * 0.15, -32.0],
             19 * 2,
             (0, 0),
@@ -79,7 +85,7 @@ impl Tutorial {
             state.font.clone(),
         );
         draw.pixtext(
-            "EMP",
+            state.lang.get("tutorial.emp"),
             [state.w * 0.3, -32.0],
             19 * 2,
             (0, 0),
@@ -89,7 +95,7 @@ impl Tutorial {
         let alpha = 255 - ((self.frame as f32 / 40.0).sin().abs() * 200.0) as u8;
 
         draw.pixtext(
-            "press SPACE to begin",
+            state.lang.get("menu.begin"),
             [state.w * 0.5 - 132.0, -state.h * 0.5 + 32.0],
             19,
             (0, 0),
@@ -110,7 +116,8 @@ impl Tutorial {
         self.frame += 1;
         self.next.take()
     }
-    pub fn pressed(&mut self, app: &App, state: &mut State, k: Key) {
+    pub fn pressed(&mut self, app: &App, state: &mut State, input: Input) {
+        let Some(k) = input.key() else { return };
         match k {
             Key::Space => {
                 state
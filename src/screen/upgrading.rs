@@ -4,6 +4,7 @@ pub struct Upgrading {
     frame: i32,
     ups: usize,
     upgrades: Vec<Upgrade>,
+    mouse: Option<Vec2>,
     pub ret: Option<Upgrade>,
 }
 
@@ -14,6 +15,7 @@ impl Upgrading {
             frame: 0,
             ups: 2,
             upgrades: Upgrade::random(player),
+            mouse: None,
             ret: None,
         }
     }
@@ -23,7 +25,7 @@ impl Upgrading {
             draw.rect()
                 .x_y(0.0, 0.0)
                 .w_h(state.w, state.h * 0.5 * p)
-                .rgba8(0, 0, 0, 230);
+                .color(state.theme.background.rgba(230));
             return;
         }
 
@@ -36,38 +38,51 @@ impl Upgrading {
         draw.rect()
             .x_y(0.0, 0.0)
             .w_h(state.w, state.h * 0.5)
-            .rgba8(0, 0, 0, 230);
+            .color(state.theme.background.rgba(230));
 
         draw.pixtext(
-            "Level up!",
+            state.lang.get("upgrade.title"),
             [0.0, (1 * 64 + 96) as f32],
             19 * 2,
             (0, 0),
             state.font.clone(),
         )
-        .rgba8(255, 255, 255, br);
+        .color(state.theme.text.rgba(br));
 
         for k in 0..self.upgrades.len() {
-            let [r, g, b, a] = [
-                255,
-                if k == self.ups { 0 } else { 255 },
-                if k == self.ups { 0 } else { 255 },
-                br,
-            ];
+            let color = if k == self.ups {
+                state.theme.accent
+            } else {
+                state.theme.text
+            };
             draw.pixtext(
-                self.upgrades[k].text(),
+                state.lang.get(self.upgrades[k].id()),
                 [0.0, ((1 - k as i32) * 64) as f32],
                 19,
                 (0, 0),
                 state.font.clone(),
             )
-            .rgba8(r, g, b, a);
+            .color(color.rgba(br));
         }
     }
     pub fn update(&mut self, app: &App, state: &mut State) {
         self.frame += 1;
+
+        let m = app.mouse.position();
+        if self.mouse.is_some_and(|last| last != m) {
+            if let Some(k) = self.hovered(m) {
+                self.ups = k;
+            }
+        }
+        self.mouse = Some(m);
     }
-    pub fn pressed(&mut self, app: &App, state: &mut State, k: Key) {
+    fn hovered(&self, m: Vec2) -> Option<usize> {
+        (0..self.upgrades.len()).find(|&k| {
+            Rect::from_x_y_w_h(0.0, ((1 - k as i32) * 64) as f32, 640.0, 64.0).contains(m)
+        })
+    }
+    pub fn pressed(&mut self, app: &App, state: &mut State, input: Input) {
+        let Some(k) = input.key() else { return };
         match k {
             Key::Up if self.ups > 0 => {
                 state.play(Sound::UiSwitch, 0.75);
@@ -84,6 +99,16 @@ impl Upgrading {
             _ => {}
         }
     }
+    pub fn mpressed(&mut self, app: &App, state: &mut State, mb: MouseButton) {
+        // the choices are still hidden behind the curtain
+        if self.frame <= 30 {
+            return;
+        }
+        if let Some(k) = self.hovered(app.mouse.position()) {
+            state.play(Sound::UiSwitch, 0.75);
+            self.ret = Some(self.upgrades[k]);
+        }
+    }
 }
 
 #[repr(i32)]
@@ -136,29 +161,30 @@ impl Upgrade {
         }
         res
     }
-    fn text(self) -> &'static str {
+    // id of the description in the language table
+    fn id(self) -> &'static str {
         use Upgrade::*;
         match self {
-            MaxAmmo => "+3 max ammo",
-            MaxBomb => "+1 max bomb",
-            MaxTurret => "+1 max turret",
-            MaxEmp => "+1 max EMP",
+            MaxAmmo => "upgrade.max_ammo",
+            MaxBomb => "upgrade.max_bomb",
+            MaxTurret => "upgrade.max_turret",
+            MaxEmp => "upgrade.max_emp",
 
-            NotConsumeAmmo => "+20% chance to not consume ammo", //3
-            PierceAmmo => "+1 bullet pierce",
-            ForkAmmo => "+1 bullet fork",
-            Sniper => "slower reload and +1 bullet damage",
+            NotConsumeAmmo => "upgrade.not_consume_ammo", //3
+            PierceAmmo => "upgrade.pierce_ammo",
+            ForkAmmo => "upgrade.fork_ammo",
+            Sniper => "upgrade.sniper",
 
-            DmgBomb => "+3 bomb damage",
-            RadAndFuseBomb => "+1 bomb radius, +1s bomb fuse time",
-            ScoreBomb => "+3 score on kill with bomb",
+            DmgBomb => "upgrade.dmg_bomb",
+            RadAndFuseBomb => "upgrade.rad_and_fuse_bomb",
+            ScoreBomb => "upgrade.score_bomb",
 
-            CdTurret => "faster reload for turrets", //3
-            DmgTurret => "+1 turret damage",         //3
+            CdTurret => "upgrade.cd_turret",   //3
+            DmgTurret => "upgrade.dmg_turret", //3
 
-            MovSpd => "increased movement speed", //3
+            MovSpd => "upgrade.mov_spd", //3
 
-            DurEmp => "+5s EMP duration",
+            DurEmp => "upgrade.dur_emp",
         }
     }
     pub fn apply(self, player: &mut Player) {
@@ -3,10 +3,19 @@ use super::*;
 const KEY: u8 = 0b11001010;
 
 #[derive(Serialize, Deserialize)]
+#[serde(default)]
 pub struct Storage {
     pub highscore: i32,
-    pub hotkeys: Vec<u32>,
+    pub bindings: Bindings,
     pub achievements: HashMap<Achievement, AchievementState>,
+    pub display: Display,
+    pub language: Language,
+    pub map: MapSource,
+    pub map_size: MapSize,
+    pub map_shape: Shape,
+    // the hotkey list from before bindings, read once and folded into them
+    #[serde(skip_serializing)]
+    hotkeys: Option<Vec<u32>>,
 }
 
 impl Storage {
@@ -22,8 +31,15 @@ impl Storage {
 
         let decrypted = String::from_utf8(raw).ok()?;
 
-        let parsed = json::from_str(&*decrypted).ok()?;
-        Some(parsed)
+        let parsed: Storage = json::from_str(&*decrypted).ok()?;
+        Some(parsed.migrated())
+    }
+    // a save from before bindings gets its hotkeys carried over
+    fn migrated(mut self) -> Self {
+        if let Some(keys) = self.hotkeys.take() {
+            self.bindings = Bindings::legacy(&keys);
+        }
+        self
     }
     #[must_use]
     pub fn save(&self) -> std::io::Result<()> {
@@ -53,18 +69,14 @@ impl Default for Storage {
         ]);
         Storage {
             highscore: 0,
-            hotkeys: vec![
-                Key::Up as u32,
-                Key::Left as u32,
-                Key::Down as u32,
-                Key::Right as u32,
-                Key::LControl as u32,
-                Key::Q as u32,
-                Key::E as u32,
-                Key::R as u32,
-                Key::W as u32,
-            ],
+            bindings: Bindings::default(),
             achievements,
+            display: Display::default(),
+            language: Language::English,
+            map: MapSource::Generated(Generator::Classic),
+            map_size: MapSize::Large,
+            map_shape: Shape::Square,
+            hotkeys: None,
         }
     }
 }
@@ -83,3 +95,81 @@ pub enum Achievement {
 pub struct AchievementState {
     pub state: bool,
 }
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum WindowMode {
+    Fullscreen,
+    Borderless,
+    Windowed,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct Display {
+    pub mode: WindowMode,
+    pub size: (u32, u32),
+    pub monitor: usize,
+    pub vsync: bool,
+    pub zoom: f32,
+    pub particles: bool,
+    pub shake: bool,
+    pub theme: Preset,
+    // extra marks on tiles so nothing depends on color alone
+    pub shapes: bool,
+    // shots and turrets aim at the cursor instead of the last direction held
+    pub mouse_aim: bool,
+}
+
+impl Default for Display {
+    fn default() -> Self {
+        Display {
+            mode: WindowMode::Borderless,
+            size: (1920, 1080),
+            monitor: 0,
+            vsync: true,
+            zoom: 1.0,
+            particles: true,
+            shake: true,
+            theme: Preset::Default,
+            shapes: false,
+            mouse_aim: false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn old_hotkeys_become_bindings() {
+        let keys: Vec<_> = [
+            Key::W,
+            Key::A,
+            Key::S,
+            Key::D,
+            Key::F,
+            Key::Q,
+            Key::E,
+            Key::R,
+            Key::LShift,
+        ]
+        .iter()
+        .map(|&k| k as u32)
+        .collect();
+        let old = format!(r#"{{"highscore":12,"hotkeys":{:?}}}"#, keys);
+        let storage = json::from_str::<Storage>(&old).unwrap().migrated();
+        let b = &storage.bindings;
+        let none = HashSet::new();
+
+        assert_eq!(storage.highscore, 12);
+        assert_eq!(b.action(Input::Key(Key::W), &none), Some(Action::Up));
+        assert_eq!(b.action(Input::Key(Key::LShift), &none), Some(Action::Run));
+        assert_eq!(b.action(Input::Pad(Pad::DPadUp), &none), Some(Action::Up));
+        // f was the default for use, the old shoot key keeps it
+        assert_eq!(b.action(Input::Key(Key::F), &none), Some(Action::Shoot));
+        assert_eq!(b.get(Action::Use)[0], None);
+        assert_eq!(b.action(Input::Key(Key::P), &none), Some(Action::Pause));
+        assert!(!json::to_string(&storage).unwrap().contains("hotkeys"));
+    }
+}
@@ -0,0 +1,243 @@
+use super::*;
+
+// user theme, read from the working directory like `storage`
+const THEME_FILE: &str = "theme.json";
+
+pub type Color = [u8; 3];
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Preset {
+    Default,
+    Deuteranopia,
+    Protanopia,
+    HighContrast,
+    Custom,
+}
+
+impl Preset {
+    pub const ALL: [Preset; 5] = [
+        Preset::Default,
+        Preset::Deuteranopia,
+        Preset::Protanopia,
+        Preset::HighContrast,
+        Preset::Custom,
+    ];
+}
+
+// every color the game draws with, missing keys in a file fall back to the default
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct Theme {
+    pub background: Color,
+    pub floor: Color,
+    pub wall: Color,
+    pub movable_wall: Color,
+    pub door: Color,
+    pub water: Color,
+    pub spikes: Color,
+    pub barricade: Color,
+    pub player: Color,
+    pub zombie: Color,
+    pub boss: Color,
+    pub bomb: Color,
+    pub blast: Color,
+    pub turret: Color,
+    pub turret_charged: Color,
+    pub bullet: Color,
+    pub bullet_old: Color,
+    pub emp: Color,
+
+    pub ammo: Color,
+    pub bombs: Color,
+    pub turrets: Color,
+    pub emps: Color,
+    pub food: Color,
+
+    pub text: Color,
+    pub dim: Color,
+    pub accent: Color,
+    pub danger: Color,
+    pub bar: Color,
+    pub health: Color,
+    pub score: Color,
+    pub marker: Color,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme {
+            background: [0, 0, 0],
+            floor: [48, 48, 48],
+            wall: [255, 165, 0],
+            movable_wall: [200, 150, 100],
+            door: [139, 90, 43],
+            water: [30, 90, 160],
+            spikes: [170, 170, 170],
+            barricade: [160, 110, 50],
+            player: [255, 255, 255],
+            zombie: [0, 255, 0],
+            boss: [100, 0, 100],
+            bomb: [255, 0, 0],
+            blast: [255, 0, 0],
+            turret: [0, 0, 255],
+            turret_charged: [255, 0, 0],
+            bullet: [255, 0, 0],
+            bullet_old: [255, 255, 0],
+            emp: [0x89, 0xD1, 0xFE],
+
+            ammo: [255, 165, 0],
+            bombs: [255, 0, 0],
+            turrets: [0, 0, 255],
+            emps: [128, 0, 128],
+            food: [64, 224, 208],
+
+            text: [255, 255, 255],
+            dim: [128, 128, 128],
+            accent: [255, 0, 0],
+            danger: [128, 0, 0],
+            bar: [39, 39, 39],
+            health: [200, 0, 0],
+            score: [255, 215, 0],
+            marker: [0, 255, 0],
+        }
+    }
+}
+
+impl Theme {
+    pub fn load(preset: Preset) -> Self {
+        match preset {
+            Preset::Default => Theme::default(),
+            Preset::Deuteranopia => Theme::deuteranopia(),
+            Preset::Protanopia => Theme::protanopia(),
+            Preset::HighContrast => Theme::high_contrast(),
+            Preset::Custom => Theme::file().unwrap_or_default(),
+        }
+    }
+    pub fn file() -> Option<Self> {
+        let raw = std::fs::read_to_string(THEME_FILE).ok()?;
+        json::from_str(&raw).ok()
+    }
+    // blue and orange carry the distinctions red and green used to
+    fn deuteranopia() -> Self {
+        Theme {
+            floor: [40, 40, 40],
+            wall: [230, 159, 0],
+            movable_wall: [170, 130, 90],
+            water: [0, 80, 130],
+            zombie: [86, 180, 233],
+            boss: [204, 121, 167],
+            bomb: [213, 94, 0],
+            blast: [240, 228, 66],
+            turret: [0, 114, 178],
+            turret_charged: [240, 228, 66],
+            bullet: [240, 228, 66],
+            bullet_old: [255, 255, 255],
+            emp: [86, 180, 233],
+
+            ammo: [240, 228, 66],
+            bombs: [213, 94, 0],
+            turrets: [0, 114, 178],
+            emps: [204, 121, 167],
+            food: [0, 158, 115],
+
+            accent: [230, 159, 0],
+            danger: [213, 94, 0],
+            health: [213, 94, 0],
+            score: [240, 228, 66],
+            marker: [240, 228, 66],
+            ..Theme::default()
+        }
+    }
+    // reds look dark, so nothing important relies on them
+    fn protanopia() -> Self {
+        Theme {
+            floor: [40, 40, 40],
+            wall: [0, 114, 178],
+            movable_wall: [90, 140, 190],
+            door: [150, 110, 60],
+            water: [60, 60, 110],
+            zombie: [230, 159, 0],
+            boss: [204, 121, 167],
+            bomb: [240, 228, 66],
+            blast: [255, 255, 255],
+            turret: [86, 180, 233],
+            turret_charged: [240, 228, 66],
+            bullet: [240, 228, 66],
+            bullet_old: [255, 255, 255],
+            emp: [86, 180, 233],
+
+            ammo: [240, 228, 66],
+            bombs: [230, 159, 0],
+            turrets: [86, 180, 233],
+            emps: [204, 121, 167],
+            food: [0, 158, 115],
+
+            accent: [240, 228, 66],
+            danger: [230, 159, 0],
+            health: [240, 228, 66],
+            score: [255, 255, 255],
+            marker: [86, 180, 233],
+            ..Theme::default()
+        }
+    }
+    fn high_contrast() -> Self {
+        Theme {
+            floor: [20, 20, 20],
+            wall: [255, 255, 255],
+            movable_wall: [160, 160, 160],
+            door: [200, 120, 0],
+            water: [0, 0, 220],
+            spikes: [255, 0, 0],
+            barricade: [255, 160, 0],
+            player: [255, 255, 0],
+            zombie: [0, 255, 0],
+            boss: [255, 0, 255],
+            bomb: [255, 0, 0],
+            blast: [255, 255, 255],
+            turret: [0, 128, 255],
+            turret_charged: [255, 0, 0],
+            bullet: [255, 255, 0],
+            bullet_old: [255, 255, 0],
+            emp: [0, 255, 255],
+
+            ammo: [255, 255, 0],
+            bombs: [255, 0, 0],
+            turrets: [0, 128, 255],
+            emps: [255, 0, 255],
+            food: [0, 255, 0],
+
+            dim: [160, 160, 160],
+            accent: [255, 255, 0],
+            danger: [255, 0, 0],
+            bar: [64, 64, 64],
+            health: [255, 0, 0],
+            score: [255, 255, 0],
+            ..Theme::default()
+        }
+    }
+}
+
+pub trait ColorExt {
+    fn rgb(self) -> Rgb8;
+    fn rgba(self, a: u8) -> Rgba8;
+    // linear blend from `self` at 0 to `other` at 1
+    fn mix(self, other: Color, t: f32) -> Color;
+    // scales the color towards black
+    fn shade(self, v: f32) -> Color;
+}
+
+impl ColorExt for Color {
+    fn rgb(self) -> Rgb8 {
+        rgb8(self[0], self[1], self[2])
+    }
+    fn rgba(self, a: u8) -> Rgba8 {
+        Rgba8::new(self[0], self[1], self[2], a)
+    }
+    fn mix(self, other: Color, t: f32) -> Color {
+        let t = t.clamp(0.0, 1.0);
+        [0, 1, 2].map(|k| (self[k] as f32 + (other[k] as f32 - self[k] as f32) * t) as u8)
+    }
+    fn shade(self, v: f32) -> Color {
+        self.map(|k| (k as f32 * v.clamp(0.0, 1.0)) as u8)
+    }
+}